@@ -0,0 +1,245 @@
+use crate::cache::{Cache, CacheMode};
+use crate::managers::{self, query_all, PackageManager};
+use crate::output::OutputFormat;
+use crate::{PackageResult, PackageStatus};
+use std::io::{self, IsTerminal};
+use std::process::Command;
+use std::time::Duration;
+
+/// Something to do with an installed package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Update,
+    Remove,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Update => "update",
+            Action::Remove => "remove",
+        }
+    }
+
+    /// Returns the command line for the action, `None` if the manager can't do it.
+    fn command(&self, manager: &dyn PackageManager, result: &PackageResult) -> Option<Vec<String>> {
+        match self {
+            Action::Update => manager.update_command(result),
+            Action::Remove => manager.remove_command(result),
+        }
+    }
+}
+
+/// Formats a command line for display, quoting arguments with spaces.
+pub fn display(line: &[String]) -> String {
+    line.iter()
+        .map(|arg| {
+            if arg.contains(char::is_whitespace) {
+                format!("'{}'", arg)
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Runs a command line, its output goes straight to the terminal.
+/// Returns true if it succeeded.
+pub fn run(line: &[String]) -> io::Result<bool> {
+    let Some((program, args)) = line.split_first() else {
+        return Err(io::Error::other("empty command line"));
+    };
+    debug!("running: {}", display(line));
+    let status = Command::new(program).args(args).status()?;
+    Ok(status.success())
+}
+
+/// Asks the manager again after it changed something and prints the new state.
+fn recheck(manager: &dyn PackageManager, query: &str, cache: &Cache) -> io::Result<()> {
+    cache.invalidate(manager.name());
+    let result = match cache.with_mode(CacheMode::Refresh).query(manager, query) {
+        Ok(result) => result,
+        Err(e) => PackageResult::error(manager.name(), query, &e),
+    };
+    OutputFormat::Pretty
+        .renderer()
+        .render(&[result.for_query(query)])
+}
+
+/// Offers to install every package that isn't installed yet with one of the
+/// managers that have it available, in the order of the results.
+pub fn prompt_install(results: &[PackageResult], cache: &Cache) -> io::Result<()> {
+    let mut queries: Vec<&str> = vec![];
+    for result in results {
+        if !queries.contains(&result.query.as_str()) {
+            queries.push(&result.query);
+        }
+    }
+
+    for query in queries {
+        // similar names are only shown, not offered
+        let group: Vec<&PackageResult> = results
+            .iter()
+            .filter(|result| result.query == query && result.distance.is_none())
+            .collect();
+        if group.iter().any(|result| result.status.is_installed()) {
+            continue;
+        }
+
+        let candidates: Vec<(&PackageResult, &dyn PackageManager, Vec<String>)> = group
+            .into_iter()
+            .filter(|result| result.status == PackageStatus::Available)
+            .filter_map(|result| {
+                let manager = managers::find(&result.manager)?;
+                let line = manager.install_command(result)?;
+                Some((result, manager, line))
+            })
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+
+        let mut select = cliclack::select(format!("Install {}?", query));
+        for (index, (result, _, line)) in candidates.iter().enumerate() {
+            select = select.item(
+                Some(index),
+                format!("{} ({})", result.manager, result.version_info()),
+                display(line),
+            );
+        }
+        let Some(index) = select.item(None, "skip", "").interact()? else {
+            continue;
+        };
+
+        let (_, manager, line) = &candidates[index];
+        cliclack::log::step(format!("Running {}", display(line)))?;
+        if !run(line)? {
+            cliclack::log::error(format!(
+                "[ {} ] - installing {} failed",
+                manager.name(),
+                query
+            ))?;
+            continue;
+        }
+        recheck(*manager, query, cache)?;
+    }
+
+    Ok(())
+}
+
+/// Updates or removes packages with the managers that installed them.
+///
+/// If several managers own a package the user is asked which one to use,
+/// unless `only_manager` picks one. With `dry_run` the command lines are
+/// printed instead of run. Returns false if anything failed.
+pub fn run_action(
+    action: Action,
+    packages: &[String],
+    only_manager: Option<&str>,
+    dry_run: bool,
+    cache: &Cache,
+    timeout: Duration,
+) -> io::Result<bool> {
+    let managers: Vec<&'static dyn PackageManager> = managers::installed_managers()
+        .into_iter()
+        .filter(|manager| only_manager.is_none_or(|name| manager.name() == name))
+        .collect();
+    if let Some(name) = only_manager
+        && managers.is_empty()
+    {
+        eprintln!("{} is not an installed manager", name);
+        return Ok(false);
+    }
+
+    // acting on stale results would be wrong, so always ask the managers
+    let cache = cache.with_mode(CacheMode::Refresh);
+    let results = query_all(&managers, packages, &cache, timeout, |_| {});
+    let mut success = true;
+
+    for query in packages {
+        let owners: Vec<(&PackageResult, &dyn PackageManager, Vec<String>)> = results
+            .iter()
+            .filter(|result| result.query == *query && result.status.is_installed())
+            .filter_map(|result| {
+                let manager = managers::find(&result.manager)?;
+                let line = action.command(manager, result)?;
+                Some((result, manager, line))
+            })
+            .collect();
+
+        let pinned: Vec<&PackageResult> = results
+            .iter()
+            .filter(|result| result.query == *query && result.pinned)
+            .collect();
+        if !pinned.is_empty() && matches!(action, Action::Update) {
+            if owners.is_empty() {
+                eprintln!("{} is pinned, unpin it to update it", query);
+                success = false;
+                continue;
+            }
+            for result in &pinned {
+                eprintln!("{} is pinned in {}, skipping it", query, result.manager);
+            }
+        }
+
+        if owners.is_empty() {
+            eprintln!(
+                "{} is not installed with any manager that can {} it",
+                query,
+                action.name()
+            );
+            success = false;
+            continue;
+        }
+
+        if dry_run {
+            for (_, _, line) in &owners {
+                println!("{}", display(line));
+            }
+            continue;
+        }
+
+        let index = if owners.len() == 1 {
+            0
+        } else if io::stdin().is_terminal() {
+            let mut select = cliclack::select(format!("{} {} with?", action.name(), query));
+            for (index, (result, _, line)) in owners.iter().enumerate() {
+                select = select.item(
+                    index,
+                    format!("{} ({})", result.manager, result.version_info()),
+                    display(line),
+                );
+            }
+            select.interact()?
+        } else {
+            eprintln!(
+                "{} is installed with {}, pick one with --manager",
+                query,
+                owners
+                    .iter()
+                    .map(|(result, _, _)| result.manager.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            success = false;
+            continue;
+        };
+
+        let (_, manager, line) = &owners[index];
+        cliclack::log::step(format!("Running {}", display(line)))?;
+        if !run(line)? {
+            cliclack::log::error(format!(
+                "[ {} ] - {} {} failed",
+                manager.name(),
+                action.name(),
+                query
+            ))?;
+            success = false;
+            continue;
+        }
+        recheck(*manager, query, &cache)?;
+    }
+
+    Ok(success)
+}
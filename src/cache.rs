@@ -0,0 +1,389 @@
+use crate::managers::PackageManager;
+use crate::{PackageResult, PackageStatus};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long cached results stay valid by default.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How the cache is used for a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// use fresh entries, ask the manager otherwise
+    Normal,
+    /// always ask the manager and update the cache
+    Refresh,
+    /// never ask the manager, only use cached entries (even expired ones)
+    Offline,
+}
+
+/// A cached value with the time it was stored.
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    timestamp: u64,
+    /// seconds the entry stays fresh if it's shorter than the ttl of the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl: Option<u64>,
+    data: T,
+}
+
+/// Numbers about the cache shown by `boss cache stats`.
+pub struct CacheStats {
+    pub results: usize,
+    pub installed_lists: usize,
+    pub searches: usize,
+    pub expired: usize,
+    pub bytes: u64,
+}
+
+/// The on-disk cache for query results and installed-list snapshots.
+///
+/// Results are stored per manager and package under `results/<manager>/`,
+/// installed lists per manager under `installed/` and search results per
+/// manager and term under `search/<manager>/`.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// how long results of packages that weren't found stay fresh, if not the ttl
+    miss_ttl: Option<Duration>,
+    mode: CacheMode,
+}
+
+/// Returns the cache directory, `$XDG_CACHE_HOME/boss` or `~/.cache/boss`.
+pub fn cache_dir() -> PathBuf {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(".cache")
+        }
+    };
+    base.join("boss")
+}
+
+/// Seconds since the unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns when the manager's database was last changed, in seconds since the
+/// unix epoch. A directory counts as changed when any of its entries did.
+fn database_changed(manager: &dyn PackageManager) -> Option<u64> {
+    let database = manager.database()?;
+    let modified = |path: &Path| -> Option<u64> {
+        let modified = fs::metadata(path).ok()?.modified().ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+    };
+    read_dir(&database)
+        .iter()
+        .filter_map(|path| modified(path))
+        .chain(modified(&database))
+        .max()
+}
+
+/// Counts the writes of this process, to give each its own temporary file.
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// The longest file name written, well below the 255 bytes most file systems allow.
+const MAX_FILE_NAME: usize = 200;
+
+/// Turns a package name into a safe file name. Other characters are percent
+/// encoded, so different names never share a file, and long names are cut
+/// with a hash of the whole name appended.
+fn file_name(name: &str) -> String {
+    let mut safe = String::new();
+    for (index, byte) in name.bytes().enumerate() {
+        let keep = byte.is_ascii_alphanumeric()
+            || b"-_+@".contains(&byte)
+            // a leading dot would hide the file
+            || (byte == b'.' && index > 0);
+        if keep {
+            safe.push(byte as char);
+        } else {
+            safe.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    if safe.len() > MAX_FILE_NAME {
+        // FNV-1a, it only has to be stable, not secure
+        let hash = name.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+        safe.truncate(MAX_FILE_NAME - 17);
+        safe = format!("{}-{:016x}", safe, hash);
+    }
+    format!("{}.json", safe)
+}
+
+impl Cache {
+    pub fn new(ttl: Duration, mode: CacheMode) -> Self {
+        Cache {
+            dir: cache_dir(),
+            ttl,
+            miss_ttl: None,
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// Returns the same cache with a different mode.
+    pub fn with_mode(&self, mode: CacheMode) -> Self {
+        Cache {
+            mode,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the same cache that keeps packages that weren't found for a shorter time,
+    /// e.g. for guessed names that will rarely be asked for again.
+    pub fn with_miss_ttl(&self, miss_ttl: Duration) -> Self {
+        Cache {
+            miss_ttl: Some(miss_ttl),
+            ..self.clone()
+        }
+    }
+
+    fn result_path(&self, manager: &str, package_name: &str) -> PathBuf {
+        self.dir
+            .join("results")
+            .join(manager)
+            .join(file_name(package_name))
+    }
+
+    fn search_path(&self, manager: &str, term: &str) -> PathBuf {
+        self.dir.join("search").join(manager).join(file_name(term))
+    }
+
+    fn installed_path(&self, manager: &str) -> PathBuf {
+        self.dir.join("installed").join(file_name(manager))
+    }
+
+    fn is_fresh(&self, timestamp: u64, ttl: Option<u64>) -> bool {
+        let ttl = ttl.map_or(self.ttl.as_secs(), |ttl| ttl.min(self.ttl.as_secs()));
+        now().saturating_sub(timestamp) < ttl
+    }
+
+    /// Reads an entry, only if it's fresh unless we are offline. Entries from
+    /// before the manager's database last `changed` are stale as well.
+    fn read<T: DeserializeOwned>(&self, path: &Path, changed: Option<u64>) -> Option<T> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+        let content = fs::read_to_string(path).ok()?;
+        let entry: Entry<T> = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("ignoring broken cache entry {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        if self.mode == CacheMode::Offline {
+            debug!("cache hit: {}", path.display());
+            return Some(entry.data);
+        }
+        // the same second could be before or after the change, so it's stale too
+        if changed.is_some_and(|changed| entry.timestamp <= changed) {
+            debug!(
+                "cache outdated by an install or removal: {}",
+                path.display()
+            );
+            None
+        } else if self.is_fresh(entry.timestamp, entry.ttl) {
+            debug!("cache hit: {}", path.display());
+            Some(entry.data)
+        } else {
+            debug!("cache expired: {}", path.display());
+            None
+        }
+    }
+
+    /// Writes an entry, failures are only logged since the cache is optional.
+    /// It's written to a temporary file first, so readers never see half of it.
+    fn write<T: Serialize>(&self, path: &Path, data: T, ttl: Option<Duration>) {
+        let entry = Entry {
+            timestamp: now(),
+            ttl: ttl.map(|ttl| ttl.as_secs()),
+            data,
+        };
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        // several threads can write the same entry, so every write gets its own file
+        let count = WRITES.fetch_add(1, Ordering::Relaxed);
+        temp_name.push(format!(".{}.{}.tmp", std::process::id(), count));
+        let temp_path = path.with_file_name(temp_name);
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| Ok(serde_json::to_string(&entry)?))
+            .and_then(|content| fs::write(&temp_path, content))
+            .and_then(|_| fs::rename(&temp_path, path));
+        if let Err(e) = result {
+            warn!("could not write cache entry {}: {}", path.display(), e);
+            let _ = fs::remove_file(&temp_path);
+        }
+    }
+
+    /// Queries a manager for a package, going through the cache.
+    pub fn query(
+        &self,
+        manager: &dyn PackageManager,
+        package_name: &str,
+    ) -> Result<PackageResult, String> {
+        let path = self.result_path(manager.name(), package_name);
+        if let Some(result) = self.read(&path, database_changed(manager)) {
+            return Ok(result);
+        }
+        if self.mode == CacheMode::Offline {
+            return Ok(PackageResult::error(
+                manager.name(),
+                package_name,
+                "not in cache (offline)",
+            ));
+        }
+
+        let result = manager.query(package_name)?;
+        // errors and timeouts are worth retrying next time
+        if !matches!(
+            result.status,
+            PackageStatus::Error | PackageStatus::TimedOut
+        ) {
+            let ttl = match result.status {
+                PackageStatus::NotFound => self.miss_ttl,
+                _ => None,
+            };
+            self.write(&path, &result, ttl);
+        }
+        Ok(result)
+    }
+
+    /// Lists the installed packages of a manager, going through the cache.
+    pub fn list_installed(
+        &self,
+        manager: &dyn PackageManager,
+    ) -> Result<Vec<PackageResult>, String> {
+        let path = self.installed_path(manager.name());
+        if let Some(installed) = self.read(&path, database_changed(manager)) {
+            return Ok(installed);
+        }
+        if self.mode == CacheMode::Offline {
+            return Err(format!(
+                "[{}] installed list not in cache (offline)",
+                manager.name()
+            ));
+        }
+
+        let installed = manager.list_installed()?;
+        self.write(&path, &installed, None);
+        Ok(installed)
+    }
+
+    /// Searches a manager for a term, going through the cache.
+    pub fn search(
+        &self,
+        manager: &dyn PackageManager,
+        term: &str,
+    ) -> Result<Vec<PackageResult>, String> {
+        let path = self.search_path(manager.name(), term);
+        if let Some(found) = self.read(&path, database_changed(manager)) {
+            return Ok(found);
+        }
+        if self.mode == CacheMode::Offline {
+            return Err(format!(
+                "[{}] search not in cache (offline)",
+                manager.name()
+            ));
+        }
+
+        let found = manager.search(term)?;
+        self.write(&path, &found, None);
+        Ok(found)
+    }
+
+    /// Removes every cached entry of a manager, e.g. after installing something.
+    pub fn invalidate(&self, manager: &str) {
+        let _ = fs::remove_dir_all(self.dir.join("results").join(manager));
+        let _ = fs::remove_dir_all(self.dir.join("search").join(manager));
+        let _ = fs::remove_file(self.installed_path(manager));
+    }
+
+    /// Removes the whole cache and returns the number of removed entries.
+    pub fn clear(&self) -> io::Result<usize> {
+        let stats = self.stats();
+        match fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(stats.results + stats.installed_lists + stats.searches),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Counts the entries and size of the cache.
+    pub fn stats(&self) -> CacheStats {
+        let mut stats = CacheStats {
+            results: 0,
+            installed_lists: 0,
+            searches: 0,
+            expired: 0,
+            bytes: 0,
+        };
+
+        // the kind of entry: 0 for results, 1 for installed lists, 2 for searches
+        let mut files = vec![];
+        for manager_dir in read_dir(&self.dir.join("results")) {
+            files.extend(read_dir(&manager_dir).into_iter().map(|file| (file, 0)));
+        }
+        files.extend(
+            read_dir(&self.dir.join("installed"))
+                .into_iter()
+                .map(|file| (file, 1)),
+        );
+        for manager_dir in read_dir(&self.dir.join("search")) {
+            files.extend(read_dir(&manager_dir).into_iter().map(|file| (file, 2)));
+        }
+
+        for (file, kind) in files {
+            match kind {
+                0 => stats.results += 1,
+                1 => stats.installed_lists += 1,
+                _ => stats.searches += 1,
+            }
+            stats.bytes += fs::metadata(&file)
+                .map(|meta| meta.len())
+                .unwrap_or_default();
+
+            let (timestamp, ttl) = fs::read_to_string(&file)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Entry<serde_json::Value>>(&content).ok())
+                .map(|entry| (entry.timestamp, entry.ttl))
+                .unwrap_or_default();
+            if !self.is_fresh(timestamp, ttl) {
+                stats.expired += 1;
+            }
+        }
+
+        stats
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Returns the paths in a directory, nothing if it doesn't exist.
+fn read_dir(dir: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
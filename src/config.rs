@@ -0,0 +1,248 @@
+use crate::cache;
+use crate::output::OutputFormat;
+use crate::DEFAULT_TIMEOUT;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The config of this run, set once at startup.
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Written to the config file by `boss config edit` if it doesn't exist yet.
+const TEMPLATE: &str = r#"# boss config, see `boss config show` for the current values
+
+# only check these managers, all installed managers if empty
+# enabled = ["apt", "cargo"]
+
+# never check these managers
+# disabled = ["snap"]
+
+# preference ranking, managers that aren't listed follow in the default order
+# order = ["cargo", "apt", "nix"]
+
+# default output format: pretty, plain, markdown, table, json or ndjson
+# output = "table"
+
+# seconds to wait for each manager
+# timeout = 30
+
+# seconds to keep cached results
+# cache_ttl = 86400
+
+# binary path and extra arguments per manager
+# [managers.apt]
+# path = "/usr/bin/apt"
+# args = ["-o", "APT::Cache-Limit=100000000"]
+
+# look up the latest versions of installed go binaries through a GOPROXY,
+# or set check_latest = false to not look them up at all
+# [managers.go]
+# source = "file:///srv/goproxy"
+# check_latest = false
+"#;
+
+/// The user config, read from `~/.config/boss/config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// only check these managers, all if empty
+    pub enabled: Vec<String>,
+    /// never check these managers
+    pub disabled: Vec<String>,
+    /// preference ranking of managers, first is preferred
+    pub order: Vec<String>,
+    /// default output format, detected if not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// seconds to wait for each manager
+    pub timeout: u64,
+    /// seconds to keep cached results
+    pub cache_ttl: u64,
+    /// binary path and extra arguments per manager
+    pub managers: BTreeMap<String, ManagerConfig>,
+}
+
+/// Settings for a single manager.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManagerConfig {
+    /// the binary to run instead of looking it up in the PATH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// arguments passed before every command
+    pub args: Vec<String>,
+    /// registry, index or proxy to use instead of the default, for managers that support it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// whether to look up the latest versions online, for managers where it's optional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_latest: Option<bool>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: vec![],
+            disabled: vec![],
+            order: vec![],
+            output: None,
+            timeout: DEFAULT_TIMEOUT,
+            cache_ttl: cache::DEFAULT_TTL.as_secs(),
+            managers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Returns the config file path, `$BOSS_CONFIG` or `$XDG_CONFIG_HOME/boss/config.toml`.
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("BOSS_CONFIG") {
+        return PathBuf::from(path);
+    }
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(".config")
+        }
+    };
+    base.join("boss").join("config.toml")
+}
+
+/// Splits a comma separated env var into a list.
+fn env_list(name: &str) -> Option<Vec<String>> {
+    let value = std::env::var(name).ok()?;
+    Some(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Reads a number from an env var.
+fn env_number(name: &str) -> Result<Option<u64>, String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| format!("{}: {}", name, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+impl Config {
+    /// Loads the config file (if any) and applies the `BOSS_*` env vars on top.
+    pub fn load() -> Result<Self, String> {
+        let path = config_path();
+        let mut config = match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| format!("invalid config {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(format!("could not read {}: {}", path.display(), e)),
+        };
+
+        if let Some(enabled) = env_list("BOSS_ENABLED") {
+            config.enabled = enabled;
+        }
+        if let Some(disabled) = env_list("BOSS_DISABLED") {
+            config.disabled = disabled;
+        }
+        if let Some(order) = env_list("BOSS_ORDER") {
+            config.order = order;
+        }
+        if let Ok(output) = std::env::var("BOSS_OUTPUT") {
+            config.output = Some(output);
+        }
+        if let Some(timeout) = env_number("BOSS_TIMEOUT")? {
+            config.timeout = timeout;
+        }
+        if let Some(cache_ttl) = env_number("BOSS_CACHE_TTL")? {
+            config.cache_ttl = cache_ttl;
+        }
+
+        if let Some(output) = &config.output
+            && OutputFormat::from_name(output).is_none()
+        {
+            return Err(format!(
+                "unknown output format {:?}, expected one of {}",
+                output,
+                OutputFormat::NAMES.join(", ")
+            ));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns true if the manager should be checked.
+    pub fn is_enabled(&self, manager: &str) -> bool {
+        (self.enabled.is_empty() || self.enabled.iter().any(|name| name == manager))
+            && !self.disabled.iter().any(|name| name == manager)
+    }
+
+    /// Position of the manager in the preference ranking, unlisted managers come last.
+    pub fn preference(&self, manager: &str) -> usize {
+        self.order
+            .iter()
+            .position(|name| name == manager)
+            .unwrap_or(self.order.len())
+    }
+
+    /// The settings of a manager, empty if it's not configured.
+    pub fn manager(&self, manager: &str) -> ManagerConfig {
+        self.managers.get(manager).cloned().unwrap_or_default()
+    }
+
+    /// Serializes the config as it would be written to the config file.
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string(self).map_err(|e| e.to_string())
+    }
+}
+
+/// Sets the config for this run, can only be done once.
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        warn!("config was already set");
+    }
+}
+
+/// Returns the config of this run, the default if none was set.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Opens the config file in `$VISUAL` or `$EDITOR`, creating it from a template first.
+pub fn edit() -> io::Result<()> {
+    let path = config_path();
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, TEMPLATE)?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // the editor can come with arguments, like `code --wait`
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+
+    // tell the user right away if the edit broke the config
+    Config::load().map(|_| ()).map_err(io::Error::other)
+}
@@ -0,0 +1,111 @@
+//! `boss list`, everything installed with every manager in one list.
+
+use crate::cache::Cache;
+use crate::managers::{list_all, PackageManager};
+use crate::output::OutputFormat;
+use crate::{PackageResult, PackageStatus};
+use console::style;
+use std::io;
+use std::time::Duration;
+
+/// Lists the installed packages of all given managers, sorted by manager and name.
+/// With `manual_only` the packages that were installed as a dependency are left out.
+pub fn run_list(
+    managers: &[&'static dyn PackageManager],
+    cache: &Cache,
+    timeout: Duration,
+    format: OutputFormat,
+    manual_only: bool,
+) -> io::Result<()> {
+    let listers: Vec<&'static dyn PackageManager> = managers
+        .iter()
+        .copied()
+        .filter(|manager| manager.capabilities().list_installed)
+        .collect();
+
+    let results = if format == OutputFormat::Pretty {
+        println!();
+        cliclack::intro(style(" boss ").on_cyan().black())?;
+        let spinner = cliclack::spinner();
+        spinner.start(format!("Listing {} managers...", listers.len()));
+        let results = list_all(&listers, cache, timeout, |manager| {
+            spinner.set_message(format!("{} done", manager));
+        });
+        spinner.stop("Listed:");
+        results
+    } else {
+        list_all(&listers, cache, timeout, |_| {})
+    };
+
+    let mut installed = vec![];
+    let mut failed = vec![];
+    for result in results {
+        match result.status {
+            PackageStatus::Error | PackageStatus::TimedOut => failed.push(result),
+            PackageStatus::InstalledAutomatic if manual_only => {}
+            // nothing was asked for, which also shows the package column in tables
+            _ => installed.push(result.for_query("")),
+        }
+    }
+    let position = |result: &PackageResult| {
+        listers
+            .iter()
+            .position(|manager| manager.name() == result.manager)
+    };
+    installed.sort_by(|a, b| {
+        position(a)
+            .cmp(&position(b))
+            .then_with(|| a.package.cmp(&b.package))
+    });
+
+    if format != OutputFormat::Pretty {
+        // keep the output parseable, failures go to stderr
+        for result in &failed {
+            eprintln!(
+                "{}: {}",
+                result.manager,
+                result.error.as_deref().unwrap_or("timed out")
+            );
+        }
+        // the list is long, so it's often cut short with `head`
+        return match format.renderer().render(&installed) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        };
+    }
+
+    for result in &failed {
+        match result.status {
+            PackageStatus::TimedOut => {
+                cliclack::log::warning(format!("[ {} ] - [timed out]", result.manager))?
+            }
+            _ => cliclack::log::error(format!(
+                "[ {} ] - [error] - {}",
+                result.manager,
+                result.error.as_deref().unwrap_or_default()
+            ))?,
+        }
+    }
+    for manager in &listers {
+        let count = installed
+            .iter()
+            .filter(|result| result.manager == manager.name())
+            .count();
+        if count > 0 {
+            cliclack::log::remark(format!(
+                "{}: {} package{}",
+                manager.name(),
+                count,
+                if count > 1 { "s" } else { "" }
+            ))?;
+        }
+    }
+    cliclack::outro(format!(
+        "{} package{} installed",
+        installed.len(),
+        if installed.len() == 1 { "" } else { "s" }
+    ))?;
+
+    // thousands of packages read better as a table than as log lines
+    OutputFormat::Table.renderer().render(&installed)
+}
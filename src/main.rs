@@ -18,58 +18,285 @@ use simplelog::*;
 
 use std::fs::File;
 use std::process::{Command, Output, Stdio};
+use std::time::Duration;
 
 use std::collections::HashMap;
 
 // import the managers module
 mod managers;
-use managers::{check_apt, check_cargo, check_nix, check_snap, check_yay};
+use managers::{installed_managers, query_all, PackageManager};
 
-// TODO enum of managers, maybe create a type for each manager for better handeling
+mod output;
+use output::OutputFormat;
+use serde::{Deserialize, Serialize};
+
+mod cache;
+use cache::{Cache, CacheMode};
+
+mod config;
+use config::Config;
+
+mod actions;
+use actions::Action;
+
+mod version;
+use version::Scheme;
+
+mod similar;
+
+mod search;
+
+mod list;
+
+/// Seconds to wait for a manager before reporting it as timed out.
+const DEFAULT_TIMEOUT: u64 = 30;
+
+/// The state of a package for a single manager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PackageStatus {
+    /// the manager doesn't know the package
+    NotFound,
+    /// not installed, but available to download
+    Available,
+    /// installed and up to date (or the latest version is unknown)
+    Installed,
+    /// installed, but a newer version is available
+    InstalledOutdated,
+    /// installed as a dependency of another package
+    InstalledAutomatic,
+    /// only a virtual package, provided by other packages
+    Virtual,
+    /// the manager failed to check the package
+    Error,
+    /// the manager didn't answer in time
+    TimedOut,
+}
+
+impl PackageStatus {
+    /// Returns true for every installed state.
+    fn is_installed(&self) -> bool {
+        matches!(
+            self,
+            PackageStatus::Installed
+                | PackageStatus::InstalledOutdated
+                | PackageStatus::InstalledAutomatic
+        )
+    }
+
+    /// Returns true if the package was found at all, installed or not.
+    fn is_found(&self) -> bool {
+        self.is_installed() || *self == PackageStatus::Available
+    }
+
+    /// Stable identifier of the status, used for machine readable output.
+    fn as_str(&self) -> &'static str {
+        match self {
+            PackageStatus::NotFound => "not_found",
+            PackageStatus::Available => "available",
+            PackageStatus::Installed => "installed",
+            PackageStatus::InstalledOutdated => "installed_outdated",
+            PackageStatus::InstalledAutomatic => "installed_automatic",
+            PackageStatus::Virtual => "virtual",
+            PackageStatus::Error => "error",
+            PackageStatus::TimedOut => "timed_out",
+        }
+    }
+
+    /// Position of the status when sorting results, lower comes first.
+    fn rank(&self) -> u8 {
+        match self {
+            PackageStatus::InstalledOutdated => 0,
+            PackageStatus::Installed => 1,
+            PackageStatus::InstalledAutomatic => 2,
+            PackageStatus::Available => 3,
+            PackageStatus::Virtual => 4,
+            PackageStatus::NotFound => 5,
+            PackageStatus::Error => 6,
+            PackageStatus::TimedOut => 7,
+        }
+    }
+}
+
+impl std::fmt::Display for PackageStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            PackageStatus::NotFound => "not found",
+            PackageStatus::Available => "available",
+            PackageStatus::Installed => "installed",
+            PackageStatus::InstalledOutdated => "outdated",
+            PackageStatus::InstalledAutomatic => "installed,automatic",
+            PackageStatus::Virtual => "virtual",
+            PackageStatus::Error => "error",
+            PackageStatus::TimedOut => "timed out",
+        };
+        write!(f, "{}", label)
+    }
+}
 
 /// Represents the result of a package query.
+#[derive(Serialize, Deserialize)]
 struct PackageResult {
-    manager: String, // apt, yay, go, cargo
-    package: String, // name only
-    version: String, // version
-    desc: String,    // description
-    repo: String,    // repo, for yay it's the repo (for go it's the module path?)
-    status: String,  // installed, available, not found; TODO: create an enum
+    /// apt, yay, go, cargo
+    manager: String,
+    /// the name that was asked for
+    query: String,
+    /// name only
+    package: String,
+    /// installed, available, not found, ...
+    status: PackageStatus,
+    /// local version, if installed
+    installed_version: Option<String>,
+    /// newest version in the registry, if known
+    latest_version: Option<String>,
+    /// short description, empty if the manager has none
+    #[serde(rename = "description")]
+    desc: String,
+    /// repo, for yay it's the repo (for go it's the module path?)
+    repo: String,
+    /// where it's installed, like user or system
+    location: Option<String>,
+    /// held at its version, updates are skipped
+    #[serde(default)]
+    pinned: bool,
+    /// edit distance to the query, only for similar names
+    #[serde(default)]
+    distance: Option<usize>,
+    /// error message if the check failed
+    error: Option<String>,
+}
+
+/// Turns an empty string into `None`.
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
 }
 
 impl PackageResult {
-    fn some(
+    fn new(manager: &str, package: &str, status: PackageStatus) -> Self {
+        PackageResult {
+            manager: manager.to_string(),
+            query: package.to_string(),
+            package: package.to_string(),
+            status,
+            installed_version: None,
+            latest_version: None,
+            desc: "".to_string(),
+            repo: "".to_string(),
+            location: None,
+            pinned: false,
+            distance: None,
+            error: None,
+        }
+    }
+
+    /// A package that is installed, outdated if the latest version is newer
+    /// by the version scheme of the manager.
+    fn installed(
         manager: &str,
         package: &str,
-        status: &str,
-        version: &str,
+        installed_version: &str,
+        latest_version: &str,
         desc: &str,
         repo: &str,
     ) -> Self {
+        let status = if !installed_version.is_empty()
+            && !latest_version.is_empty()
+            && Scheme::of(manager)
+                .compare(installed_version, latest_version)
+                .is_lt()
+        {
+            PackageStatus::InstalledOutdated
+        } else {
+            PackageStatus::Installed
+        };
         PackageResult {
-            manager: manager.to_string(),
-            package: package.to_string(),
-            status: status.to_string(),
-            version: version.to_string(),
+            installed_version: non_empty(installed_version),
+            latest_version: non_empty(latest_version),
             desc: desc.to_string(),
             repo: repo.to_string(),
+            ..PackageResult::new(manager, package, status)
+        }
+    }
+
+    /// A package that is not installed, but can be downloaded.
+    fn available(
+        manager: &str,
+        package: &str,
+        latest_version: &str,
+        desc: &str,
+        repo: &str,
+    ) -> Self {
+        PackageResult {
+            latest_version: non_empty(latest_version),
+            desc: desc.to_string(),
+            repo: repo.to_string(),
+            ..PackageResult::new(manager, package, PackageStatus::Available)
         }
     }
 
     fn none(manager: &str, package: &str) -> Self {
+        PackageResult::new(manager, package, PackageStatus::NotFound)
+    }
+
+    /// A check that failed, keeping the error message.
+    fn error(manager: &str, package: &str, error: &str) -> Self {
         PackageResult {
-            manager: manager.to_string(),
-            package: package.to_string(),
-            status: "not found".to_string(),
-            version: "".to_string(),
-            desc: "".to_string(),
-            repo: "".to_string(),
+            error: Some(error.to_string()),
+            ..PackageResult::new(manager, package, PackageStatus::Error)
         }
     }
-}
 
-/// Type alias for the check function signature.
-type CheckFn = fn(&str) -> Result<PackageResult, String>;
+    /// A check that didn't finish in time.
+    fn timed_out(manager: &str, package: &str) -> Self {
+        PackageResult::new(manager, package, PackageStatus::TimedOut)
+    }
+
+    /// Sets the name that was asked for, the package name can differ from it.
+    fn for_query(mut self, query: &str) -> Self {
+        self.query = query.to_string();
+        self
+    }
+
+    /// Sets where the package is installed, like a user or system installation.
+    fn with_location(mut self, location: &str) -> Self {
+        self.location = non_empty(location);
+        self
+    }
+
+    /// Marks the package as pinned to its installed version.
+    fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Marks the package as a similar name to the query, not the exact one.
+    fn with_distance(mut self, distance: usize) -> Self {
+        self.distance = Some(distance);
+        self
+    }
+
+    /// Overrides the status, e.g. for virtual or automatically installed packages.
+    fn with_status(mut self, status: PackageStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Formats the versions for display, like `1.0 -> 1.1` for outdated packages.
+    fn version_info(&self) -> String {
+        match (&self.installed_version, &self.latest_version) {
+            (Some(local), Some(latest)) if self.status == PackageStatus::InstalledOutdated => {
+                format!("{} -> {}", local, latest)
+            }
+            (Some(local), _) => local.clone(),
+            (None, Some(latest)) => latest.clone(),
+            (None, None) => "".to_string(),
+        }
+    }
+}
 
 struct MyTheme;
 impl Theme for MyTheme {
@@ -103,27 +330,6 @@ fn reduce_whitespace(s: String) -> String {
     result
 }
 
-/// Returns a list of installed package managers.
-fn get_installed_managers() -> Vec<&'static str> {
-    let managers = vec!["snap", "apt", "yay", "cargo", "go", "nix"];
-    let mut installed_managers = Vec::new();
-
-    for manager in &managers {
-        if let Ok(status) = Command::new("which")
-            .arg(manager)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-        {
-            if status.success() {
-                installed_managers.push(*manager)
-            }
-        }
-    }
-
-    installed_managers
-}
-
 /// Checks the output of a command and returns the lines of output if successful.
 fn check_output(output: Output) -> Result<Vec<String>, String> {
     if output.stdout.is_empty() {
@@ -146,66 +352,74 @@ fn check_output(output: Output) -> Result<Vec<String>, String> {
     Result::Ok(lines)
 }
 
-/// Returns a map of package manager names to their corresponding check functions.
-fn get_check_functions() -> HashMap<&'static str, CheckFn> {
-    let mut map: HashMap<&'static str, CheckFn> = HashMap::new();
-
-    map.insert("apt", check_apt as CheckFn);
-    map.insert("yay", check_yay as CheckFn);
-    map.insert("snap", check_snap as CheckFn);
-    map.insert("nix", check_nix as CheckFn);
-    map.insert("cargo", check_cargo as CheckFn);
-
-    map
+/// How the results of a package are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    /// outdated and installed first, then available and not found
+    Status,
+    /// newest version first, results without a version last
+    Version,
 }
 
-/// Returns a vector of sorted package results.
-fn sort_results(results: Vec<PackageResult>) -> Vec<PackageResult> {
-    let mut installed: Vec<PackageResult> = Vec::new();
-    let mut available: Vec<PackageResult> = Vec::new();
-    let mut not_found: Vec<PackageResult> = Vec::new();
-
-    for result in results {
-        if result.status.contains("installed") {
-            installed.push(result);
-        } else if result.status == "available" {
-            available.push(result);
-        } else {
-            not_found.push(result);
+impl SortKey {
+    const NAMES: [&'static str; 2] = ["status", "version"];
+
+    fn from_name(name: &str) -> Option<SortKey> {
+        match name {
+            "status" => Some(SortKey::Status),
+            "version" => Some(SortKey::Version),
+            _ => None,
         }
     }
+}
 
-    installed.append(&mut available);
-    installed.append(&mut not_found);
-    installed
+/// The newest version known for a result, the latest one or else the installed one.
+fn newest_version(result: &PackageResult) -> Option<&str> {
+    result
+        .latest_version
+        .as_deref()
+        .or(result.installed_version.as_deref())
 }
 
-/// Prints the results to the console using cliclack.
-fn print_result(results: Vec<PackageResult>) -> core::result::Result<(), std::io::Error> {
-    for result in results {
-        if result.status.contains("installed") {
-            cliclack::log::success(format!(
-                "[ {} ] - [{}] - ({})",
-                result.manager, result.status, result.version
-            ))?;
-        } else if result.status == "available" {
-            // cliclack::log::info(format!(
-            //     "[ {} ] - [available] - ({})",
-            //     result.manager, result.version
-            // ))?;
-            cliclack::note(
-                format!(
-                    "[ {} ] - [available] - ({})",
-                    result.manager, result.version
-                ),
-                result.desc,
-            )?;
-        } else if result.status == "not found" {
-            cliclack::log::error(format!("[ {} ] - [not found]", result.manager))?;
+/// Returns a vector of sorted package results.
+fn sort_results(mut results: Vec<PackageResult>, key: SortKey) -> Vec<PackageResult> {
+    // keep the packages in the order they were given and sort within each of them,
+    // the sort is stable so managers keep their order within a status
+    let mut queries: Vec<String> = vec![];
+    for result in &results {
+        if !queries.contains(&result.query) {
+            queries.push(result.query.clone());
         }
     }
+    let position = |result: &PackageResult| queries.iter().position(|query| *query == result.query);
+    results.sort_by(|a, b| {
+        // exact results first, similar names after them, the closest first
+        let ordering = position(a)
+            .cmp(&position(b))
+            .then_with(|| a.distance.cmp(&b.distance));
+        let ordering = match key {
+            SortKey::Status => ordering,
+            // versions of different managers can only be compared loosely
+            SortKey::Version => {
+                ordering.then_with(|| match (newest_version(a), newest_version(b)) {
+                    (Some(a), Some(b)) => Scheme::Semver.compare(b, a),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
+                })
+            }
+        };
+        ordering.then_with(|| a.status.rank().cmp(&b.status.rank()))
+    });
+    results
+}
 
-    Ok(())
+/// Returns true if the package was found by any manager.
+fn is_found(results: &[PackageResult], package_name: &str) -> bool {
+    results
+        .iter()
+        .filter(|result| result.query == package_name)
+        .any(|result| result.status.is_found())
 }
 
 fn cli() -> CliCommand {
@@ -256,6 +470,16 @@ fn cli() -> CliCommand {
                 .action(ArgAction::SetTrue)
                 .conflicts_with("interactive"),
         )
+        .arg(
+            Arg::new("require")
+                .long("require")
+                .value_name("MODE")
+                .help("With --quiet, return 0 if all or any of the packages are found")
+                .value_parser(["all", "any"])
+                .default_value("all")
+                .action(ArgAction::Set)
+                .requires("quiet"),
+        )
         .arg(
             Arg::new("interactive")
                 .short('i')
@@ -265,6 +489,330 @@ fn cli() -> CliCommand {
                 .conflicts_with("quiet")
                 .conflicts_with("package"),
         )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FORMAT")
+                .help("How to print the results [default: pretty, plain if not a terminal]")
+                .value_parser(OutputFormat::NAMES.to_vec())
+                .action(ArgAction::Set)
+                .conflicts_with("quiet"),
+        )
+        .arg(
+            Arg::new("similar")
+                .long("similar")
+                .help("Also look for similar names, like pkg-cli, pkg-bin or near matches")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("quiet"),
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .value_name("KEY")
+                .help("How to order the results of each package")
+                .value_parser(SortKey::NAMES.to_vec())
+                .default_value("status")
+                .action(ArgAction::Set)
+                .conflicts_with("quiet"),
+        )
+        .arg(
+            Arg::new("refresh")
+                .long("refresh")
+                .help("Ignore cached results and ask the managers again")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("offline"),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .help("Only use cached results, even expired ones")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("refresh"),
+        )
+        .arg(
+            Arg::new("timeout")
+                .short('t')
+                .long("timeout")
+                .value_name("SECS")
+                .help(format!(
+                    "Seconds to wait for each manager [default: {}]",
+                    DEFAULT_TIMEOUT
+                ))
+                .value_parser(clap::value_parser!(u64))
+                .action(ArgAction::Set),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            CliCommand::new("cache")
+                .about("Manage the cached results")
+                .subcommand_required(true)
+                .subcommand(CliCommand::new("clear").about("Remove all cached results"))
+                .subcommand(CliCommand::new("stats").about("Show what is cached")),
+        )
+        .subcommand(action_cli(
+            "update",
+            "Update packages with the manager that installed them",
+        ))
+        .subcommand(action_cli(
+            "remove",
+            "Remove packages with the manager that installed them",
+        ))
+        .subcommand(
+            CliCommand::new("search")
+                .about("Search every manager for packages matching a term")
+                .arg(
+                    Arg::new("term")
+                        .required(true)
+                        .help("A part of the name or description")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("interactive")
+                        .short('i')
+                        .long("interactive")
+                        .help("Show the results a page at a time")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FORMAT")
+                        .help("How to print the results [default: pretty, plain if not a terminal]")
+                        .value_parser(OutputFormat::NAMES.to_vec())
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("manager")
+                        .short('m')
+                        .long("manager")
+                        .value_name("MANAGER")
+                        .help("Only search with this manager")
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            CliCommand::new("list")
+                .about("List everything installed with every manager")
+                .arg(
+                    Arg::new("manager")
+                        .short('m')
+                        .long("manager")
+                        .value_name("MANAGER")
+                        .help("Only list the packages of this manager, can be repeated")
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("manual")
+                        .long("manual")
+                        .help("Only list packages that were installed on purpose, not as a dependency")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FORMAT")
+                        .help("How to print the results [default: pretty, plain if not a terminal]")
+                        .value_parser(OutputFormat::NAMES.to_vec())
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("refresh")
+                        .long("refresh")
+                        .help("Ignore cached lists and ask the managers again")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            CliCommand::new("config")
+                .about("Show or edit the config file")
+                .subcommand_required(true)
+                .subcommand(CliCommand::new("show").about("Print the current config"))
+                .subcommand(CliCommand::new("path").about("Print the path of the config file"))
+                .subcommand(CliCommand::new("edit").about("Open the config file in $EDITOR")),
+        )
+}
+
+/// Builds the `update` and `remove` subcommands, they take the same args.
+fn action_cli(name: &'static str, about: &'static str) -> CliCommand {
+    CliCommand::new(name)
+        .about(about)
+        .arg(
+            Arg::new("package")
+                .num_args(1..)
+                .required(true)
+                .help("The package(s) to act on")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .short('n')
+                .long("dry-run")
+                .help("Print the commands instead of running them")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("manager")
+                .short('m')
+                .long("manager")
+                .value_name("MANAGER")
+                .help("Only use this manager")
+                .action(ArgAction::Set),
+        )
+}
+
+/// Runs the `update` or `remove` subcommand.
+fn run_action(
+    action: Action,
+    matches: &ArgMatches,
+    cache: &Cache,
+    timeout: Duration,
+) -> std::io::Result<()> {
+    let packages: Vec<String> = matches
+        .get_many::<String>("package")
+        .map(|vals| vals.cloned().collect())
+        .unwrap_or_default();
+    let success = actions::run_action(
+        action,
+        &packages,
+        matches.get_one::<String>("manager").map(String::as_str),
+        matches.get_flag("dry-run"),
+        cache,
+        timeout,
+    )?;
+    if !success {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Runs the `search` subcommand.
+fn run_search(matches: &ArgMatches, cache: &Cache, timeout: Duration) -> std::io::Result<()> {
+    let term = matches
+        .get_one::<String>("term")
+        .expect("clap requires a term");
+    let only_manager = matches.get_one::<String>("manager");
+    let managers: Vec<&'static dyn PackageManager> = installed_managers()
+        .into_iter()
+        .filter(|manager| only_manager.is_none_or(|name| manager.name() == name))
+        .collect();
+    if let Some(name) = only_manager
+        && managers.is_empty()
+    {
+        eprintln!("{} is not an installed manager", name);
+        std::process::exit(1);
+    }
+
+    let format = matches
+        .get_one::<String>("output")
+        .or(config::get().output.as_ref())
+        .and_then(|name| OutputFormat::from_name(name))
+        .unwrap_or_else(OutputFormat::detect);
+    if format == OutputFormat::Pretty {
+        cliclack::set_theme(MyTheme);
+    }
+    search::run_search(
+        term,
+        &managers,
+        cache,
+        timeout,
+        format,
+        matches.get_flag("interactive"),
+    )
+}
+
+/// Runs the `list` subcommand.
+fn run_list(matches: &ArgMatches, cache: &Cache, timeout: Duration) -> std::io::Result<()> {
+    let only_managers: Vec<&String> = matches
+        .get_many::<String>("manager")
+        .map(|names| names.collect())
+        .unwrap_or_default();
+    let managers: Vec<&'static dyn PackageManager> = installed_managers()
+        .into_iter()
+        .filter(|manager| {
+            only_managers.is_empty() || only_managers.iter().any(|name| manager.name() == *name)
+        })
+        .collect();
+    for name in &only_managers {
+        if !managers.iter().any(|manager| manager.name() == *name) {
+            eprintln!("{} is not an installed manager", name);
+            std::process::exit(1);
+        }
+    }
+
+    let cache = if matches.get_flag("refresh") {
+        cache.with_mode(CacheMode::Refresh)
+    } else {
+        cache.clone()
+    };
+    let format = matches
+        .get_one::<String>("output")
+        .or(config::get().output.as_ref())
+        .and_then(|name| OutputFormat::from_name(name))
+        .unwrap_or_else(OutputFormat::detect);
+    if format == OutputFormat::Pretty {
+        cliclack::set_theme(MyTheme);
+    }
+    list::run_list(
+        &managers,
+        &cache,
+        timeout,
+        format,
+        matches.get_flag("manual"),
+    )
+}
+
+/// Loads the config, exits with a readable error if it's broken.
+fn load_config() -> Config {
+    match Config::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs the `config` subcommand.
+fn run_config(matches: &ArgMatches) -> std::io::Result<()> {
+    match matches.subcommand() {
+        Some(("show", _)) => {
+            let settings = load_config();
+            print!("{}", settings.to_toml().map_err(std::io::Error::other)?);
+        }
+        Some(("path", _)) => println!("{}", config::config_path().display()),
+        Some(("edit", _)) => {
+            if let Err(e) = config::edit() {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        _ => unreachable!("clap requires a config subcommand"),
+    }
+    Ok(())
+}
+
+/// Runs the `cache` subcommand.
+fn run_cache(matches: &ArgMatches, cache: &Cache) -> std::io::Result<()> {
+    match matches.subcommand() {
+        Some(("clear", _)) => {
+            let removed = cache.clear()?;
+            println!("removed {} entries from {}", removed, cache.dir().display());
+        }
+        Some(("stats", _)) => {
+            let stats = cache.stats();
+            println!("path:            {}", cache.dir().display());
+            println!("results:         {}", stats.results);
+            println!("installed lists: {}", stats.installed_lists);
+            println!("searches:        {}", stats.searches);
+            println!("expired:         {}", stats.expired);
+            println!("size:            {:.1} KiB", stats.bytes as f64 / 1024.0);
+        }
+        _ => unreachable!("clap requires a cache subcommand"),
+    }
+    Ok(())
 }
 
 fn main() -> std::io::Result<()> {
@@ -291,8 +839,48 @@ fn main() -> std::io::Result<()> {
     let matches = cli().try_get_matches().unwrap_or_else(|e| e.exit());
     debug!("Matches: {:?}", matches);
 
-    let packages: Vec<&str> = match matches.get_many::<String>("package") {
-        Some(vals) => vals.map(|v| v.as_str()).collect(),
+    if let Some(("config", sub_matches)) = matches.subcommand() {
+        return run_config(sub_matches);
+    }
+
+    let settings = load_config();
+    debug!("Config: {:?}", settings);
+    config::init(settings);
+    let settings = config::get();
+
+    let cache_mode = if matches.get_flag("refresh") {
+        CacheMode::Refresh
+    } else if matches.get_flag("offline") {
+        CacheMode::Offline
+    } else {
+        CacheMode::Normal
+    };
+    let cache = Cache::new(Duration::from_secs(settings.cache_ttl), cache_mode);
+
+    let timeout = Duration::from_secs(
+        matches
+            .get_one::<u64>("timeout")
+            .copied()
+            .unwrap_or(settings.timeout),
+    );
+
+    match matches.subcommand() {
+        Some(("cache", sub_matches)) => return run_cache(sub_matches, &cache),
+        Some(("update", sub_matches)) => {
+            return run_action(Action::Update, sub_matches, &cache, timeout);
+        }
+        Some(("remove", sub_matches)) => {
+            return run_action(Action::Remove, sub_matches, &cache, timeout);
+        }
+        Some(("search", sub_matches)) => return run_search(sub_matches, &cache, timeout),
+        Some(("list", sub_matches)) => return run_list(sub_matches, &cache, timeout),
+        _ => (),
+    }
+
+    let is_interactive = matches.get_flag("interactive");
+    let packages: Vec<String> = match matches.get_many::<String>("package") {
+        Some(vals) => vals.cloned().collect(),
+        None if is_interactive => vec![],
         None => {
             eprintln!("No packages provided.");
             std::process::exit(1);
@@ -300,7 +888,6 @@ fn main() -> std::io::Result<()> {
     };
     debug!("Packages: {:?}", packages);
 
-    let is_interactive = matches.get_flag("interactive");
     let stay_quiet = matches.get_flag("quiet");
 
     // check for missing args
@@ -328,88 +915,125 @@ fn main() -> std::io::Result<()> {
     // }
 
     if stay_quiet {
-        let package_name = packages.first().unwrap().to_string();
-        let check_functions = get_check_functions();
-        let mut results = vec![];
-
-        for manager in get_installed_managers() {
-            if let Some(check_fn) = check_functions.get(manager) {
-                match check_fn(&package_name) {
-                    Ok(result) => results.push(result),
-                    Err(e) => {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            format!("Error: {}", e),
-                        ));
-                    }
-                }
-            }
-        }
+        let results = query_all(&installed_managers(), &packages, &cache, timeout, |_| {});
+        let mut found = packages
+            .iter()
+            .map(|package_name| is_found(&results, package_name));
 
-        if results.iter().all(|result| result.status == "not found") {
-            std::process::exit(1);
-        } else {
+        let success = match matches.get_one::<String>("require").map(String::as_str) {
+            Some("any") => found.any(|found| found),
+            _ => found.all(|found| found),
+        };
+        if success {
             return Ok(());
+        } else {
+            std::process::exit(1);
         }
     }
 
+    let format = matches
+        .get_one::<String>("output")
+        .or(settings.output.as_ref())
+        .and_then(|name| OutputFormat::from_name(name))
+        .unwrap_or_else(OutputFormat::detect);
+
+    // the prompt is part of the pretty output, without it there would be no packages
+    if is_interactive && format != OutputFormat::Pretty {
+        eprintln!("--interactive needs the pretty output, use `-o pretty` when piping.");
+        std::process::exit(1);
+    }
+
+    let sort_key = matches
+        .get_one::<String>("sort")
+        .and_then(|name| SortKey::from_name(name))
+        .unwrap_or(SortKey::Status);
+
+    let find_similar = matches.get_flag("similar");
+
+    if format != OutputFormat::Pretty {
+        let managers = installed_managers();
+        let mut results = query_all(&managers, &packages, &cache, timeout, |_| {});
+        if find_similar {
+            results.extend(similar::find_similar(
+                &managers,
+                &packages,
+                &cache,
+                timeout,
+                |_| {},
+            ));
+        }
+        return format.renderer().render(&sort_results(results, sort_key));
+    }
+
     println!();
 
     cliclack::set_theme(MyTheme);
     cliclack::intro(style(" boss ").on_cyan().black())?;
 
     // get managers
-    let installed_managers = get_installed_managers();
+    let installed_managers = installed_managers();
 
     cliclack::log::remark(format!(
         "Managers: {} ({})",
-        installed_managers.join(", "),
+        installed_managers
+            .iter()
+            .map(|manager| manager.name())
+            .collect::<Vec<_>>()
+            .join(", "),
         installed_managers.len()
     ))?;
 
-    // only get the first package in the list for now
-    let package_name: String = if is_interactive {
-        match cliclack::input("Enter package name: ").interact() {
-            Ok(name) => name,
+    let packages: Vec<String> = if is_interactive {
+        match cliclack::input("Enter package name(s): ").interact::<String>() {
+            Ok(names) => names.split_whitespace().map(String::from).collect(),
             Err(e) => {
                 cliclack::log::error(e)?;
                 return Ok(());
             }
         }
     } else {
-        packages.first().unwrap().to_string()
+        packages
     };
 
     cliclack::log::remark(format!(
-        "Package: {}",
-        style(&package_name).on_black().cyan()
+        "Package{}: {}",
+        if packages.len() > 1 { "s" } else { "" },
+        packages
+            .iter()
+            .map(|package_name| style(package_name).on_black().cyan().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
     ))?;
 
-    let progress = progress_bar(installed_managers.len() as u64)
+    let progress = progress_bar((installed_managers.len() * packages.len()) as u64)
         .with_template("{msg:20} {bar:15.cyan/blue} {pos}/{len} [{elapsed}]");
     progress.start("Fetching...");
 
-    let check_functions = get_check_functions();
-    let mut results = vec![];
-
-    for manager in &installed_managers {
-        if let Some(check_fn) = check_functions.get(*manager) {
-            progress.set_message(format!("Checking {}...", manager));
-            match check_fn(&package_name) {
-                Ok(result) => {
-                    results.push(result);
-                    progress.inc(1);
-                }
-                Err(e) => {
-                    progress.error(&e);
-                    cliclack::log::error(e)?;
-                }
-            }
-        }
-    }
+    progress.set_message(format!("Checking {} managers...", installed_managers.len()));
+    let results = query_all(&installed_managers, &packages, &cache, timeout, |result| {
+        progress.set_message(format!("{} done", result.manager));
+        progress.inc(1);
+    });
 
     progress.stop("Results:");
-    print_result(sort_results(results))?;
+    let mut results = results;
+    if find_similar {
+        let spinner = cliclack::spinner();
+        spinner.start("Looking for similar names...");
+        results.extend(similar::find_similar(
+            &installed_managers,
+            &packages,
+            &cache,
+            timeout,
+            |manager| spinner.set_message(format!("{} done", manager)),
+        ));
+        spinner.stop("Similar names:");
+    }
+    let results = sort_results(results, sort_key);
+    OutputFormat::Pretty.renderer().render(&results)?;
+    if is_interactive {
+        actions::prompt_install(&results, &cache)?;
+    }
     cliclack::outro("Done!")?;
 
     Ok(())
@@ -0,0 +1,223 @@
+use super::{command, command_line, sudo, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult, PackageStatus};
+use std::path::PathBuf;
+
+/// Where apk keeps the installed packages, listed in `apk policy` like a repo.
+const INSTALLED_DB: &str = "lib/apk/db/installed";
+
+/// The Alpine `apk` package manager.
+pub struct Apk;
+
+impl PackageManager for Apk {
+    fn name(&self) -> &'static str {
+        "apk"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_apk(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_apk()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(format!("/{}", INSTALLED_DB)))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Gentoo
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("apk", &["add", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("apk", &["upgrade", &result.package])))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("apk", &["del", &result.package])))
+    }
+}
+
+/// A line of `apk list`, like
+/// `curl-8.9.1-r2 x86_64 {curl} (curl) [upgradable from: curl-8.9.0-r0]`.
+struct Line<'a> {
+    name: &'a str,
+    version: String,
+    /// the installed version, if any
+    installed: Option<String>,
+}
+
+/// Splits `name-version-release`, names can contain dashes but versions can't.
+fn split_package(package: &str) -> Option<(&str, String)> {
+    let mut parts = package.rsplitn(3, '-');
+    let release = parts.next()?;
+    let version = parts.next()?;
+    let name = parts.next()?;
+    Some((name, format!("{}-{}", version, release)))
+}
+
+fn parse_line(line: &str) -> Option<Line<'_>> {
+    let (name, version) = split_package(line.split_whitespace().next()?)?;
+    let installed = if line.ends_with("[installed]") {
+        Some(version.clone())
+    } else {
+        line.split_once("[upgradable from: ")
+            .and_then(|(_, from)| split_package(from.trim_end_matches(']')))
+            .map(|(_, version)| version)
+    };
+    Some(Line {
+        name,
+        version,
+        installed,
+    })
+}
+
+/// Returns the repository the given version comes from, from `apk policy`.
+fn repository(package_name: &str, version: &str) -> String {
+    let output = match command("apk").arg("policy").arg(package_name).output() {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("[apk] {}", e);
+            return "".to_string();
+        }
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // curl policy:
+    //   8.9.1-r2:
+    //     lib/apk/db/installed
+    //     https://dl-cdn.alpinelinux.org/alpine/v3.20/main
+    let mut current = false;
+    for line in &lines {
+        if !line.starts_with("    ") {
+            current = line.trim().trim_end_matches(':') == version;
+            continue;
+        }
+        let repo = line.trim();
+        if current && repo != INSTALLED_DB {
+            return repo.to_string();
+        }
+    }
+    "".to_string()
+}
+
+/// Checks if a package is available or installed using the `apk` package manager.
+pub fn check_apk(package_name: &str) -> Result<PackageResult, String> {
+    // -------------------------------------------------------
+    // 1. check the repos and the installed db for the package
+    // -------------------------------------------------------
+    let output = match command("apk").arg("list").arg(package_name).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[apk] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // every repo that has the package gets a line, apk takes the newest
+    let found: Vec<Line> = lines
+        .iter()
+        .filter_map(|line| parse_line(line))
+        .filter(|line| line.name == package_name)
+        .collect();
+    let Some(newest) = found
+        .iter()
+        .max_by(|a, b| Scheme::Gentoo.compare(&a.version, &b.version))
+    else {
+        return Ok(PackageResult::none("apk", package_name));
+    };
+    let installed = found.iter().find_map(|line| line.installed.clone());
+
+    // ---------------------------------------------
+    // 2. get the repo of the newest version, if any
+    // ---------------------------------------------
+    let repo = repository(package_name, &newest.version);
+    let desc = description(package_name);
+
+    match installed {
+        Some(installed) => Ok(PackageResult::installed(
+            "apk",
+            package_name,
+            &installed,
+            &newest.version,
+            &desc,
+            &repo,
+        )),
+        None => Ok(PackageResult::available(
+            "apk",
+            package_name,
+            &newest.version,
+            &desc,
+            &repo,
+        )),
+    }
+}
+
+/// Returns the description of a package, empty if there is none.
+fn description(package_name: &str) -> String {
+    // curl-8.9.1-r2 description:
+    // URL retrival utility and library
+    let output = match command("apk")
+        .arg("info")
+        .arg("--description")
+        .arg(package_name)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("[apk] {}", e);
+            return "".to_string();
+        }
+    };
+    let lines = check_output(output).unwrap_or_default();
+    lines.get(1).cloned().unwrap_or_default()
+}
+
+/// Lists all packages installed with `apk`, the ones in the world file are explicit.
+pub fn list_apk() -> Result<Vec<PackageResult>, String> {
+    let output = match command("apk").arg("list").arg("--installed").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[apk] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // the world file lists what was added on purpose, like `curl` or `curl>=8`
+    let world: Vec<String> = std::fs::read_to_string("/etc/apk/world")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|atom| {
+            atom.split(['=', '<', '>', '~', '@'])
+                .next()
+                .map(String::from)
+        })
+        .collect();
+
+    let mut installed = vec![];
+    for line in &lines {
+        let Some(parsed) = parse_line(line) else {
+            warn!("[apk] skipping line {line:?}");
+            continue;
+        };
+        let mut result = PackageResult::installed("apk", parsed.name, &parsed.version, "", "", "");
+        if !world.is_empty() && !world.iter().any(|name| name == parsed.name) {
+            result = result.with_status(PackageStatus::InstalledAutomatic);
+        }
+        installed.push(result);
+    }
+
+    Ok(installed)
+}
@@ -1,14 +1,173 @@
-use crate::{check_output, PackageResult};
-use std::process::Command;
+use super::{command, command_line, dpkg, sudo, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult, PackageStatus};
+use std::path::PathBuf;
 use strp::*;
 
+/// The `apt` package manager.
+pub struct Apt;
+
+impl PackageManager for Apt {
+    fn name(&self) -> &'static str {
+        "apt"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_apt(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_apt()
+    }
+
+    fn search(&self, term: &str) -> Result<Vec<PackageResult>, String> {
+        search_apt(term)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: true,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(dpkg::STATUS))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Debian
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("apt", &["install", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line(
+            "apt",
+            &["install", "--only-upgrade", &result.package],
+        )))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("apt", &["remove", &result.package])))
+    }
+}
+
 /// Checks if a package is available or installed using the `apt` package manager.
+/// Reads the dpkg and apt files if possible, else asks `apt`.
 pub fn check_apt(package_name: &str) -> Result<PackageResult, String> {
+    match check_apt_native(package_name) {
+        Some(result) => Ok(result),
+        None => check_apt_command(package_name),
+    }
+}
+
+/// Lists all packages installed with `apt`, from the dpkg status file if possible.
+pub fn list_apt() -> Result<Vec<PackageResult>, String> {
+    match dpkg::installed() {
+        Ok(Some(installed)) => Ok(installed.iter().map(installed_result).collect()),
+        Ok(None) => list_apt_command(),
+        Err(e) => {
+            warn!("[apt] could not read the dpkg status: {}", e);
+            list_apt_command()
+        }
+    }
+}
+
+/// Turns an installed package from the status file into a result.
+fn installed_result(stanza: &dpkg::Stanza) -> PackageResult {
+    let result = PackageResult::installed(
+        "apt",
+        &stanza.package,
+        &stanza.version,
+        "",
+        &stanza.description,
+        "",
+    );
+    if stanza.auto_installed {
+        result.with_status(PackageStatus::InstalledAutomatic)
+    } else {
+        result
+    }
+}
+
+/// Checks a package by reading `/var/lib/dpkg/status` and the `Packages` indexes
+/// in `/var/lib/apt/lists`, `None` if they can't be used.
+fn check_apt_native(package_name: &str) -> Option<PackageResult> {
+    // compressed indexes would need to be unpacked, apt is faster at that
+    let lists = dpkg::package_lists();
+    if lists.is_empty() {
+        debug!("[apt] no uncompressed package lists, asking apt");
+        return None;
+    }
+    if dpkg::has_pins() {
+        debug!("[apt] pins or a default release are set, asking apt");
+        return None;
+    }
+    let architecture = dpkg::native_architecture()?;
+
+    // --------------------------------
+    // 1. check if package is installed
+    // --------------------------------
+    let installed = match dpkg::installed_package(package_name, &architecture) {
+        Ok(installed) => installed,
+        Err(e) => {
+            debug!("[apt] could not read the dpkg status: {}", e);
+            return None;
+        }
+    };
+
+    // ---------------------------------------------------------
+    // 2. find the candidate version and its repo in the indexes
+    // ---------------------------------------------------------
+    let (candidate, provided) =
+        dpkg::candidate(&lists, package_name, &architecture, installed.is_some());
+
+    match (candidate, installed) {
+        (Some((candidate, repo)), Some(installed)) => {
+            let result = PackageResult::installed(
+                "apt",
+                package_name,
+                &installed.version,
+                &candidate.version,
+                &candidate.description,
+                &repo,
+            );
+            if installed.auto_installed && result.status == PackageStatus::Installed {
+                Some(result.with_status(PackageStatus::InstalledAutomatic))
+            } else {
+                Some(result)
+            }
+        }
+        // installed from a .deb or a source that's gone
+        (None, Some(installed)) => Some(installed_result(&installed)),
+        (Some((candidate, repo)), None) => Some(PackageResult::available(
+            "apt",
+            package_name,
+            &candidate.version,
+            &candidate.description,
+            &repo,
+        )),
+        (None, None) if provided => {
+            Some(PackageResult::none("apt", package_name).with_status(PackageStatus::Virtual))
+        }
+        (None, None) => Some(PackageResult::none("apt", package_name)),
+    }
+}
+
+/// Checks if a package is available or installed by asking `apt`.
+fn check_apt_command(package_name: &str) -> Result<PackageResult, String> {
     // -----------------------------------
     // 1. check registry if package exists
     // -----------------------------------
     debug!("checking registry for package: {}", package_name);
-    let output = match Command::new("apt").arg("show").arg(package_name).output() {
+    let output = match command("apt").arg("show").arg(package_name).output() {
         Ok(output) => output,
         Err(e) => {
             error!("could not check registry: {}", e);
@@ -31,7 +190,9 @@ pub fn check_apt(package_name: &str) -> Result<PackageResult, String> {
     // -----------------------------------
     if lines.iter().any(|line| line.contains("not a real package")) {
         debug!("package is virtual and not a real package");
-        return Result::Ok(PackageResult::none("apt", package_name));
+        return Result::Ok(
+            PackageResult::none("apt", package_name).with_status(PackageStatus::Virtual),
+        );
     }
 
     // ------------------------------------------------------
@@ -63,59 +224,122 @@ pub fn check_apt(package_name: &str) -> Result<PackageResult, String> {
     // --------------------------------
     // 3. check if package is installed
     // --------------------------------
-    let output = match Command::new("apt").arg("list").arg("--installed").output() {
+    for installed in list_apt()? {
+        if package_name == installed.package {
+            let local_version = installed.installed_version.unwrap_or_default();
+            let result = PackageResult::installed(
+                "apt",
+                &installed.package,
+                &local_version,
+                &version,
+                &desc,
+                "",
+            );
+            // being outdated is more important to show than being automatic
+            if installed.status == PackageStatus::InstalledAutomatic
+                && result.status == PackageStatus::Installed
+            {
+                return Result::Ok(result.with_status(PackageStatus::InstalledAutomatic));
+            }
+            return Result::Ok(result);
+        }
+    }
+
+    Result::Ok(PackageResult::available(
+        "apt",
+        package_name,
+        &version,
+        &desc,
+        "",
+    ))
+}
+
+/// Searches the names and descriptions of all packages using `apt search`.
+pub fn search_apt(term: &str) -> Result<Vec<PackageResult>, String> {
+    let output = match command("apt").arg("search").arg(term).output() {
         Ok(output) => output,
-        Err(e) => return Err(e.to_string()),
+        Err(e) => return Err(format!("[apt] {}", e)),
     };
+    let lines = check_output(output).unwrap_or_default();
 
-    let lines = match check_output(output) {
-        Ok(lines) => lines,
-        Err(e) => return Err(e),
+    // ripgrep/stable,now 13.0.0-4+b2 amd64 [installed,automatic]
+    //   Recursively searches directories for a regex pattern
+    let mut found: Vec<PackageResult> = vec![];
+    for line in &lines {
+        if let Some(desc) = line.strip_prefix("  ") {
+            if let Some(result) = found.last_mut()
+                && result.desc.is_empty()
+            {
+                result.desc = desc.trim().to_string();
+            }
+            continue;
+        }
+        let mut chunks = line.split_whitespace();
+        let (Some(name_suite), Some(version)) = (chunks.next(), chunks.next()) else {
+            continue;
+        };
+        // "Sorting..." and "Full Text Search..." have no suite
+        let Some((name, suite)) = name_suite.split_once('/') else {
+            continue;
+        };
+        let repo = suite.split(',').next().unwrap_or_default();
+        let flags = chunks.skip(1).collect::<Vec<_>>().join(" ");
+
+        let result = if let Some(from) = flags.strip_prefix("[upgradable from: ") {
+            PackageResult::installed("apt", name, from.trim_end_matches(']'), version, "", repo)
+        } else if flags.starts_with("[installed") {
+            let result = PackageResult::installed("apt", name, version, "", "", repo);
+            if flags.contains("automatic") {
+                result.with_status(PackageStatus::InstalledAutomatic)
+            } else {
+                result
+            }
+        } else {
+            PackageResult::available("apt", name, version, "", repo)
+        };
+        found.push(result);
+    }
+
+    Ok(found)
+}
+
+/// Lists all packages installed with `apt` by asking `apt`.
+fn list_apt_command() -> Result<Vec<PackageResult>, String> {
+    let output = match command("apt").arg("list").arg("--installed").output() {
+        Ok(output) => output,
+        Err(e) => return Err(e.to_string()),
     };
 
+    // nothing installed yet also means empty output
+    let lines = check_output(output).unwrap_or_default();
+
     let filtered_lines: Vec<String> = lines
         .iter()
-        .filter(|line| {
-            !line.starts_with("Listing")
-                && !line.starts_with(' ')
-                && !line.is_empty()
-                && line.contains(package_name)
-        })
+        .filter(|line| !line.starts_with("Listing") && !line.starts_with(' ') && !line.is_empty())
         .map(|line| line.to_string())
         .collect();
 
+    let mut installed = vec![];
     for line in &filtered_lines {
         // zlib1g/noble,now 1:1.3.dfsg-3.1ubuntu2 amd64 [installed,automatic]
         let scanned: Result<(String, String, String, String, String), _> =
             try_scan!(line => "{}/{} {} {} [{}]");
-        let (name, local_version, installed): (String, String, String) = match scanned {
-            Ok((name, _, version, _, installed)) => (name, version, installed),
-            Err(e) => return Err(format!("parsing error: {e:?}")),
+        let (name, local_version, status): (String, String, String) = match scanned {
+            Ok((name, _, version, _, status)) => (name, version, status),
+            Err(e) => {
+                warn!("[apt] skipping line {line:?}: {e:?}");
+                continue;
+            }
         };
 
-        if package_name == name {
-            let version_info: String = if local_version != version {
-                format!("{} -> {}", local_version, version)
-            } else {
-                local_version
-            };
-            return Result::Ok(PackageResult::some(
-                "apt",
-                &name,
-                &installed,
-                &version_info,
-                &desc,
-                "",
-            ));
+        let result = PackageResult::installed("apt", &name, &local_version, "", "", "");
+        // [installed,automatic] or [installed,upgradable to: 1.2]
+        if status.split(',').any(|flag| flag == "automatic") {
+            installed.push(result.with_status(PackageStatus::InstalledAutomatic));
+        } else {
+            installed.push(result);
         }
     }
 
-    Result::Ok(PackageResult::some(
-        "apt",
-        package_name,
-        "available",
-        &version,
-        &desc,
-        "",
-    ))
+    Ok(installed)
 }
@@ -0,0 +1,220 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{PackageResult, PackageStatus};
+use serde::Deserialize;
+
+/// Homebrew, including Linuxbrew, for formulae and casks.
+pub struct Brew;
+
+impl PackageManager for Brew {
+    fn name(&self) -> &'static str {
+        "brew"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_brew(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_brew()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Rpm
+    }
+
+    // brew figures out by itself if the name is a formula or a cask
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("brew", &["install", &result.package]))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        // pinned formulae are never upgraded, so offer nothing
+        if result.pinned {
+            return None;
+        }
+        Some(command_line("brew", &["upgrade", &result.package]))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("brew", &["uninstall", &result.package]))
+    }
+}
+
+/// The output of `brew info --json=v2`.
+#[derive(Deserialize)]
+struct Info {
+    #[serde(default)]
+    formulae: Vec<Formula>,
+    #[serde(default)]
+    casks: Vec<Cask>,
+}
+
+#[derive(Deserialize)]
+struct Formula {
+    name: String,
+    tap: Option<String>,
+    desc: Option<String>,
+    versions: FormulaVersions,
+    #[serde(default)]
+    revision: u32,
+    #[serde(default)]
+    installed: Vec<Keg>,
+    linked_keg: Option<String>,
+    #[serde(default)]
+    outdated: bool,
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Deserialize)]
+struct FormulaVersions {
+    stable: Option<String>,
+}
+
+/// An installed version of a formula.
+#[derive(Deserialize)]
+struct Keg {
+    version: String,
+    #[serde(default)]
+    installed_on_request: bool,
+}
+
+#[derive(Deserialize)]
+struct Cask {
+    token: String,
+    tap: Option<String>,
+    desc: Option<String>,
+    version: Option<String>,
+    installed: Option<String>,
+    #[serde(default)]
+    outdated: bool,
+}
+
+impl Formula {
+    /// The newest stable version, with the revision like brew shows it (`1.0_1`).
+    fn latest_version(&self) -> String {
+        let stable = self.versions.stable.clone().unwrap_or_default();
+        match self.revision {
+            0 => stable,
+            revision => format!("{}_{}", stable, revision),
+        }
+    }
+
+    fn into_result(self) -> PackageResult {
+        let latest = self.latest_version();
+        let desc = self.desc.unwrap_or_default();
+        let tap = self.tap.unwrap_or_default();
+
+        // the linked keg is the one in use if several versions are installed
+        let keg = match &self.linked_keg {
+            Some(linked) => self.installed.iter().find(|keg| &keg.version == linked),
+            None => None,
+        }
+        .or(self.installed.last());
+
+        let Some(keg) = keg else {
+            return PackageResult::available("brew", &self.name, &latest, &desc, &tap);
+        };
+
+        // brew knows best if it's outdated, the versions don't always compare
+        let status = if self.outdated {
+            PackageStatus::InstalledOutdated
+        } else if !keg.installed_on_request {
+            PackageStatus::InstalledAutomatic
+        } else {
+            PackageStatus::Installed
+        };
+        PackageResult::installed("brew", &self.name, &keg.version, &latest, &desc, &tap)
+            .with_status(status)
+            .with_pinned(self.pinned)
+    }
+}
+
+impl Cask {
+    fn into_result(self) -> PackageResult {
+        let latest = self.version.unwrap_or_default();
+        let desc = self.desc.unwrap_or_default();
+        let tap = self.tap.unwrap_or_default();
+
+        match self.installed {
+            Some(installed) => {
+                let status = if self.outdated {
+                    PackageStatus::InstalledOutdated
+                } else {
+                    PackageStatus::Installed
+                };
+                PackageResult::installed("brew", &self.token, &installed, &latest, &desc, &tap)
+                    .with_status(status)
+            }
+            None => PackageResult::available("brew", &self.token, &latest, &desc, &tap),
+        }
+    }
+}
+
+/// Runs `brew info --json=v2` with the given arguments, `None` if brew doesn't know the name.
+fn info(args: &[&str]) -> Result<Option<Info>, String> {
+    let output = match command("brew")
+        .arg("info")
+        .arg("--json=v2")
+        .args(args)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[brew] {}", e)),
+    };
+    // "Error: No available formula or cask with the name ..." and a failing status
+    if !output.status.success() {
+        debug!("[brew] {}", String::from_utf8_lossy(&output.stderr).trim());
+        return Ok(None);
+    }
+
+    match serde_json::from_slice(&output.stdout) {
+        Ok(info) => Ok(Some(info)),
+        Err(e) => Err(format!("[brew] {}", e)),
+    }
+}
+
+/// Checks if a formula or cask is available or installed using `brew`.
+pub fn check_brew(package_name: &str) -> Result<PackageResult, String> {
+    // -------------------------------------------------
+    // 1. look up formulae and casks with the exact name
+    // -------------------------------------------------
+    let Some(info) = info(&[package_name])? else {
+        return Ok(PackageResult::none("brew", package_name));
+    };
+
+    // -------------------------------------------------------
+    // 2. prefer the formula, like brew does for shared names
+    // -------------------------------------------------------
+    if let Some(formula) = info.formulae.into_iter().next() {
+        return Ok(formula.into_result());
+    }
+    if let Some(cask) = info.casks.into_iter().next() {
+        return Ok(cask.into_result());
+    }
+
+    Ok(PackageResult::none("brew", package_name))
+}
+
+/// Lists all formulae and casks installed with `brew`.
+pub fn list_brew() -> Result<Vec<PackageResult>, String> {
+    let Some(info) = info(&["--installed"])? else {
+        return Ok(vec![]);
+    };
+
+    let formulae = info.formulae.into_iter().map(Formula::into_result);
+    let casks = info.casks.into_iter().map(Cask::into_result);
+    Ok(formulae.chain(casks).collect())
+}
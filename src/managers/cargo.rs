@@ -1,17 +1,58 @@
+use super::{command, command_line, Capabilities, PackageManager};
 use crate::{check_output, reduce_whitespace, PackageResult};
-use std::process::Command;
 use strp::*;
 
+/// The `cargo` package manager.
+pub struct Cargo;
+
+impl PackageManager for Cargo {
+    fn name(&self) -> &'static str {
+        "cargo"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_cargo(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_cargo()
+    }
+
+    fn search(&self, term: &str) -> Result<Vec<PackageResult>, String> {
+        search_cargo(term)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: true,
+        }
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("cargo", &["install", &result.package]))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        // installing again upgrades to the latest version
+        Some(command_line("cargo", &["install", &result.package]))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("cargo", &["uninstall", &result.package]))
+    }
+}
+
 /// Checks if a package is available or installed using the `cargo` package manager.
 pub fn check_cargo(package_name: &str) -> Result<PackageResult, String> {
     // -----------------------------------
     // 1. check registry if package exists
     // -----------------------------------
-    let output = match Command::new("cargo")
-        .arg("search")
-        .arg(package_name)
-        .output()
-    {
+    let output = match command("cargo").arg("search").arg(package_name).output() {
         Ok(output) => output,
         Err(e) => return Err(format!("[cargo] {}", e)),
     };
@@ -29,7 +70,7 @@ pub fn check_cargo(package_name: &str) -> Result<PackageResult, String> {
     if lines.iter().all(|line| !line.contains(package_name)) {
         return Result::Ok(PackageResult::none("cargo", package_name));
     }
-    fn check_exact_name(line: &String, package_name: &str) -> bool {
+    fn check_exact_name(line: &str, package_name: &str) -> bool {
         let mut iter = line.split_whitespace();
         let name: &str = iter.next().unwrap_or_default();
         name == package_name
@@ -66,52 +107,114 @@ pub fn check_cargo(package_name: &str) -> Result<PackageResult, String> {
     // --------------------------------
     // 3. check if package is installed
     // --------------------------------
-    let output = match Command::new("cargo").arg("install").arg("--list").output() {
+    for installed in list_cargo()? {
+        if package_name == installed.package {
+            let local_version = installed.installed_version.unwrap_or_default();
+            return Result::Ok(PackageResult::installed(
+                "cargo",
+                &installed.package,
+                &local_version,
+                &version,
+                &desc,
+                "",
+            ));
+        }
+    }
+
+    Result::Ok(PackageResult::available(
+        "cargo",
+        package_name,
+        &version,
+        &desc,
+        "",
+    ))
+}
+
+/// Searches crates.io for crates matching the term using `cargo search`.
+pub fn search_cargo(term: &str) -> Result<Vec<PackageResult>, String> {
+    let output = match command("cargo")
+        .arg("search")
+        .arg("--limit")
+        .arg("20")
+        .arg(term)
+        .output()
+    {
         Ok(output) => output,
-        Err(e) => return Err(e.to_string()),
+        Err(e) => return Err(format!("[cargo] {}", e)),
     };
+    // no matches means empty output
+    let lines = check_output(output).unwrap_or_default();
+    let installed = list_cargo()?;
 
-    let lines = match check_output(output) {
-        Ok(lines) => lines,
-        Err(e) => return Err(e),
+    let mut found = vec![];
+    for line in &lines {
+        // ripgrep = "14.1.0"    # ripgrep is a line-oriented search tool ...
+        // ... and 123 crates more (use --limit N to see more)
+        let Some((name, rest)) = line.split_once(" = \"") else {
+            continue;
+        };
+        let Some((version, rest)) = rest.split_once('"') else {
+            warn!("[cargo] skipping line {line:?}");
+            continue;
+        };
+        let desc = rest.trim().strip_prefix('#').unwrap_or_default().trim();
+        let result = match installed.iter().find(|installed| installed.package == name) {
+            Some(installed) => PackageResult::installed(
+                "cargo",
+                name,
+                installed.installed_version.as_deref().unwrap_or_default(),
+                version,
+                desc,
+                "",
+            ),
+            None => PackageResult::available("cargo", name, version, desc, ""),
+        };
+        found.push(result);
+    }
+
+    Ok(found)
+}
+
+/// Lists all packages installed with `cargo install`.
+pub fn list_cargo() -> Result<Vec<PackageResult>, String> {
+    let output = match command("cargo").arg("install").arg("--list").output() {
+        Ok(output) => output,
+        Err(e) => return Err(e.to_string()),
     };
 
+    // nothing installed yet also means empty output
+    let lines = check_output(output).unwrap_or_default();
+
     let filtered_lines: Vec<String> = lines
         .iter()
-        .filter(|line| !line.is_empty() && !line.starts_with(' ') && line.contains(package_name))
+        .filter(|line| !line.is_empty() && !line.starts_with(' '))
         .map(|line| line.to_string())
         .collect();
 
+    let mut installed = vec![];
     for line in &filtered_lines {
-        let scanned: Result<(String, String), _> = try_scan!(line => "{} v{}:");
+        // ripgrep v14.1.0:
+        // helix-term v24.7.0 (/home/user/helix/helix-term):
+        let reduced_line = line
+            .split_whitespace()
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let scanned: Result<(String, String), _> = try_scan!(reduced_line => "{} v{}");
         let (name, local_version): (String, String) = match scanned {
-            Ok((name, version)) => (name, version),
+            Ok((name, version)) => (name, version.trim_end_matches(':').to_string()),
             Err(e) => return Err(format!("[cargo] parsing error: {e:?}")),
         };
 
-        if package_name == name {
-            let version_info: String = if local_version != version {
-                format!("{} -> {}", local_version, version)
-            } else {
-                local_version
-            };
-            return Result::Ok(PackageResult::some(
-                "cargo",
-                &name,
-                "installed",
-                &version_info,
-                &desc,
-                "",
-            ));
-        }
+        installed.push(PackageResult::installed(
+            "cargo",
+            &name,
+            &local_version,
+            "",
+            "",
+            "",
+        ));
     }
 
-    Result::Ok(PackageResult::some(
-        "cargo",
-        package_name,
-        "available",
-        &version,
-        &desc,
-        "",
-    ))
+    Ok(installed)
 }
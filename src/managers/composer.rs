@@ -0,0 +1,204 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::{config, PackageResult};
+use serde::Deserialize;
+use std::fs;
+use std::process::Output;
+
+/// Composer, only the packages installed with `composer global`.
+pub struct Composer;
+
+impl PackageManager for Composer {
+    fn name(&self) -> &'static str {
+        "composer"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_composer(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_composer()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "composer",
+            &["global", "require", &result.package],
+        ))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "composer",
+            &["global", "update", &result.package],
+        ))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "composer",
+            &["global", "remove", &result.package],
+        ))
+    }
+}
+
+/// The output of `composer show --all --format=json <package>`.
+#[derive(Deserialize)]
+struct Show {
+    #[serde(default)]
+    description: String,
+    /// newest first, including branches like `dev-main`
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+/// The output of `composer global show --format=json`.
+#[derive(Deserialize)]
+struct Installed {
+    #[serde(default)]
+    installed: Vec<InstalledPackage>,
+}
+
+#[derive(Deserialize)]
+struct InstalledPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Runs `composer show` for a package in the repositories. composer can't be
+/// told about a repository on the command line, so a configured source is
+/// passed in a throwaway `composer.json`.
+fn show(package_name: &str) -> Result<Output, String> {
+    let mut composer = command("composer");
+    composer
+        .arg("show")
+        .arg("--all")
+        .arg("--format=json")
+        .arg(package_name);
+
+    let Some(source) = config::get().manager("composer").source else {
+        return composer.output().map_err(|e| format!("[composer] {}", e));
+    };
+    let manifest = serde_json::json!({
+        "repositories": [
+            { "type": "composer", "url": source },
+            { "packagist.org": false },
+        ],
+    });
+    let path = std::env::temp_dir().join(format!(
+        "boss-composer-{}-{}.json",
+        std::process::id(),
+        package_name.replace('/', "-")
+    ));
+    if let Err(e) = fs::write(&path, manifest.to_string()) {
+        return Err(format!("[composer] {}", e));
+    }
+    let output = composer.env("COMPOSER", &path).output();
+    let _ = fs::remove_file(&path);
+    output.map_err(|e| format!("[composer] {}", e))
+}
+
+/// Checks if a package is available or installed globally using `composer`.
+pub fn check_composer(package_name: &str) -> Result<PackageResult, String> {
+    // -------------------------------------------
+    // 1. check the repositories if package exists
+    // -------------------------------------------
+    let output = show(package_name)?;
+    // "Package vendor/name not found" and a failing status
+    let found: Option<Show> = if output.status.success() {
+        match serde_json::from_slice(&output.stdout) {
+            Ok(show) => Some(show),
+            Err(e) => return Err(format!("[composer] {}", e)),
+        }
+    } else {
+        debug!(
+            "[composer] {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        None
+    };
+    let found = found.map(|show| {
+        let latest = show
+            .versions
+            .into_iter()
+            .find(|version| !version.starts_with("dev-") && !version.ends_with("-dev"))
+            .unwrap_or_default();
+        (latest, show.description)
+    });
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = list_composer()?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (found, installed) {
+        (Some((latest, desc)), Some(installed)) => Ok(PackageResult::installed(
+            "composer",
+            package_name,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &latest,
+            &desc,
+            "",
+        )),
+        (None, Some(installed)) => Ok(installed),
+        (Some((latest, desc)), None) => Ok(PackageResult::available(
+            "composer",
+            package_name,
+            &latest,
+            &desc,
+            "",
+        )),
+        (None, None) => Ok(PackageResult::none("composer", package_name)),
+    }
+}
+
+/// Lists all packages installed with `composer global`.
+pub fn list_composer() -> Result<Vec<PackageResult>, String> {
+    let output = match command("composer")
+        .arg("global")
+        .arg("show")
+        .arg("--format=json")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[composer] {}", e)),
+    };
+    // fails if nothing was ever installed globally
+    if !output.status.success() {
+        return Ok(vec![]);
+    }
+
+    let installed: Installed = match serde_json::from_slice(&output.stdout) {
+        Ok(installed) => installed,
+        Err(e) => return Err(format!("[composer] {}", e)),
+    };
+    Ok(installed
+        .installed
+        .into_iter()
+        .map(|package| {
+            PackageResult::installed(
+                "composer",
+                &package.name,
+                &package.version,
+                "",
+                &package.description,
+                "",
+            )
+        })
+        .collect())
+}
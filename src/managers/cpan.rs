@@ -0,0 +1,275 @@
+use super::{command, command_line, which, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, config, PackageResult};
+use std::path::Path;
+
+/// Lists the modules installed with cpan or cpanm (the ones with a packlist)
+/// as tab separated name and version.
+const LIST_SCRIPT: &str = r#"use ExtUtils::Installed;
+my $installed = ExtUtils::Installed->new(skip_cwd => 1);
+print $_, "\t", ($installed->version($_) // ""), "\n" for $installed->modules;"#;
+
+/// The package index of an already configured `cpan` client, only read, never updated.
+const CPAN_INDEX: &str = ".cpan/sources/modules/02packages.details.txt.gz";
+/// The package index of a mirror, relative to its root.
+const MIRROR_INDEX: &str = "modules/02packages.details.txt.gz";
+/// The MetaCPAN API, asked if there is no mirror and no local index.
+const METACPAN: &str = "https://fastapi.metacpan.org/v1/module";
+
+/// CPAN modules, installed with `cpanm` or, if it's missing, `cpan`.
+pub struct Cpan;
+
+impl PackageManager for Cpan {
+    fn name(&self) -> &'static str {
+        "cpan"
+    }
+
+    // perl ships `cpan` on most distros, so it only counts if cpan is enabled on purpose
+    fn detect(&self) -> bool {
+        which("cpanm") || (which("cpan") && config::get().enabled.iter().any(|name| name == "cpan"))
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_cpan(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_cpan()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: which("cpanm"),
+            search: false,
+        }
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Perl
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_mirror(&[&result.package]))
+    }
+
+    // installing again gets the newest version
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_mirror(&[&result.package]))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        // only cpanm can uninstall
+        if !which("cpanm") {
+            return None;
+        }
+        Some(command_line("cpanm", &["--uninstall", &result.package]))
+    }
+}
+
+/// The configured mirror, `None` to use the default one.
+fn mirror() -> Option<String> {
+    config::get().manager("cpan").source
+}
+
+/// Builds a `cpanm` or `cpan` command line that only uses the configured mirror, if any.
+fn with_mirror(args: &[&str]) -> Vec<String> {
+    let mirror = mirror();
+    let mut line: Vec<&str> = vec![];
+    if which("cpanm") {
+        if let Some(mirror) = &mirror {
+            line.extend(["--mirror", mirror, "--mirror-only"]);
+        }
+        line.extend(args);
+        command_line("cpanm", &line)
+    } else {
+        if let Some(mirror) = &mirror {
+            line.extend(["-M", mirror]);
+        }
+        line.extend(args);
+        command_line("cpan", &line)
+    }
+}
+
+/// Looks up the newest version of a module in the index of the configured mirror,
+/// the local `cpan` index or on MetaCPAN, `None` if it's not there. Neither `cpan`
+/// nor `cpanm` is run here, both write to the home directory (the first run of
+/// `cpan` even configures itself).
+fn latest_version(package_name: &str) -> Result<Option<String>, String> {
+    if let Some(mirror) = mirror() {
+        return mirror_version(&mirror, package_name);
+    }
+    match index_version(package_name)? {
+        Some(version) => Ok(version),
+        None => metacpan_version(package_name),
+    }
+}
+
+/// Looks a module up in the index of the mirror, the same one cpanm reads.
+fn mirror_version(mirror: &str, package_name: &str) -> Result<Option<String>, String> {
+    let url = format!("{}/{}", mirror.trim_end_matches('/'), MIRROR_INDEX);
+    let download = std::env::temp_dir().join(format!(
+        "boss-cpan-{}-{:?}.gz",
+        std::process::id(),
+        std::thread::current().id()
+    ));
+    // curl handles http and file:// mirrors alike
+    let output = match command("curl")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--fail")
+        .arg("--location")
+        .arg("--output")
+        .arg(&download)
+        .arg(&url)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[cpan] {}", e)),
+    };
+    let version = if output.status.success() {
+        read_index(&download, package_name)
+    } else {
+        Err(format!(
+            "[cpan] {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    };
+    let _ = std::fs::remove_file(&download);
+    version
+}
+
+/// Looks a module up in the index of `cpan`, `None` if there is no index.
+fn index_version(package_name: &str) -> Result<Option<Option<String>>, String> {
+    let Some(home) = std::env::var_os("HOME") else {
+        return Ok(None);
+    };
+    let index = Path::new(&home).join(CPAN_INDEX);
+    if !index.is_file() {
+        return Ok(None);
+    }
+    read_index(&index, package_name).map(Some)
+}
+
+/// Finds the version of a module in a gzipped `02packages.details.txt`.
+fn read_index(index: &Path, package_name: &str) -> Result<Option<String>, String> {
+    let output = match command("gzip").arg("-dc").arg(index).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[cpan] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // Moose                             2.2207  E/ET/ETHER/Moose-2.2207.tar.gz
+    Ok(lines.iter().find_map(|line| {
+        let mut chunks = line.split_whitespace();
+        if chunks.next() != Some(package_name) {
+            return None;
+        }
+        // modules without a version are listed as undef
+        chunks.next().map(|version| version.replace("undef", ""))
+    }))
+}
+
+/// Asks the MetaCPAN API for the newest version of a module.
+fn metacpan_version(package_name: &str) -> Result<Option<String>, String> {
+    let output = match command("curl")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--location")
+        .arg("--write-out")
+        .arg("\n%{http_code}")
+        .arg(format!("{}/{}", METACPAN, package_name))
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[cpan] {}", e)),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "[cpan] {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = stdout.rsplit_once('\n').unwrap_or((&stdout, ""));
+    match status {
+        "200" => {}
+        "404" => return Ok(None),
+        _ => return Err(format!("[cpan] MetaCPAN answered with {}", status)),
+    }
+
+    // {"name": "Moose.pm", "version": "2.2207", ...}
+    let module: serde_json::Value = match serde_json::from_str(body) {
+        Ok(module) => module,
+        Err(e) => return Err(format!("[cpan] parsing error: {}", e)),
+    };
+    Ok(match &module["version"] {
+        serde_json::Value::String(version) => Some(version.clone()),
+        serde_json::Value::Number(version) => Some(version.to_string()),
+        _ => Some("".to_string()),
+    })
+}
+
+/// Checks if a module is available or installed using `cpanm` or `cpan`.
+pub fn check_cpan(package_name: &str) -> Result<PackageResult, String> {
+    // ----------------------------------
+    // 1. check CPAN if the module exists
+    // ----------------------------------
+    let latest = latest_version(package_name)?;
+
+    // -------------------------------
+    // 2. check if module is installed
+    // -------------------------------
+    let installed = list_cpan()?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (latest, installed) {
+        (Some(latest), Some(installed)) => Ok(PackageResult::installed(
+            "cpan",
+            package_name,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &latest,
+            "",
+            "",
+        )),
+        (None, Some(installed)) => Ok(installed),
+        (Some(latest), None) => Ok(PackageResult::available(
+            "cpan",
+            package_name,
+            &latest,
+            "",
+            "",
+        )),
+        (None, None) => Ok(PackageResult::none("cpan", package_name)),
+    }
+}
+
+/// Lists all modules installed with `cpanm` or `cpan`.
+pub fn list_cpan() -> Result<Vec<PackageResult>, String> {
+    let output = match command("perl").arg("-e").arg(LIST_SCRIPT).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[cpan] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        // Moose	2.2207
+        let Some((name, version)) = line.split_once('\t') else {
+            warn!("[cpan] skipping line {line:?}");
+            continue;
+        };
+        // perl itself is listed as "Perl"
+        if name == "Perl" {
+            continue;
+        }
+        installed.push(PackageResult::installed("cpan", name, version, "", "", ""));
+    }
+
+    Ok(installed)
+}
@@ -0,0 +1,177 @@
+use super::{command, command_line, sudo, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult};
+use std::path::PathBuf;
+
+/// Fields of the query formats below, separated by tabs. These are real tabs
+/// and newlines, no shell is involved.
+const DNF_FORMAT: &str = "%{name}\t%{epoch}\t%{version}\t%{release}\t%{repoid}\t%{summary}\n";
+const RPM_FORMAT: &str = "%{NAME}\t%{EPOCHNUM}\t%{VERSION}\t%{RELEASE}\t%{SUMMARY}\n";
+
+/// The `dnf` package manager, with `rpm` for the installed packages.
+pub struct Dnf;
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_dnf(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_rpm(None)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/var/lib/rpm"))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Rpm
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("dnf", &["install", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("dnf", &["upgrade", &result.package])))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("dnf", &["remove", &result.package])))
+    }
+}
+
+/// Builds an `epoch:version-release` string, the epoch is left out if it's 0.
+fn full_version(epoch: &str, version: &str, release: &str) -> String {
+    match epoch {
+        "" | "0" | "(none)" => format!("{}-{}", version, release),
+        epoch => format!("{}:{}-{}", epoch, version, release),
+    }
+}
+
+/// Checks if a package is available or installed using the `dnf` package manager.
+pub fn check_dnf(package_name: &str) -> Result<PackageResult, String> {
+    // -----------------------------------
+    // 1. check repos if package exists
+    // -----------------------------------
+    let output = match command("dnf")
+        .arg("repoquery")
+        .arg("--quiet")
+        .arg("--available")
+        .arg("--latest-limit=1")
+        .arg("--queryformat")
+        .arg(DNF_FORMAT)
+        .arg(package_name)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[dnf] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // ------------------------------------------------------
+    // 2. get info about package: newest version, description
+    // ------------------------------------------------------
+    // git	0	2.46.0	1.fc40	updates	Fast Version Control System
+    // one line per architecture, they only differ in the arch
+    let mut found: Option<(String, String, String)> = None;
+    for line in &lines {
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [name, epoch, version, release, repo, summary] = columns[..] else {
+            debug!("[dnf] skipping line {line:?}");
+            continue;
+        };
+        if name == package_name {
+            found = Some((
+                full_version(epoch, version, release),
+                repo.to_string(),
+                summary.to_string(),
+            ));
+            break;
+        }
+    }
+
+    // --------------------------------
+    // 3. check if package is installed
+    // --------------------------------
+    let installed = list_rpm(Some(package_name))?.into_iter().next();
+
+    match (found, installed) {
+        (Some((version, repo, desc)), Some(installed)) => {
+            let local_version = installed.installed_version.unwrap_or_default();
+            Ok(PackageResult::installed(
+                "dnf",
+                package_name,
+                &local_version,
+                &version,
+                &desc,
+                &repo,
+            ))
+        }
+        // the installed version is the newest one, or it's not from a repo
+        (None, Some(installed)) => Ok(installed),
+        (Some((version, repo, desc)), None) => Ok(PackageResult::available(
+            "dnf",
+            package_name,
+            &version,
+            &desc,
+            &repo,
+        )),
+        (None, None) => Ok(PackageResult::none("dnf", package_name)),
+    }
+}
+
+/// Lists installed packages with `rpm`, all of them or only the given one.
+pub fn list_rpm(package_name: Option<&str>) -> Result<Vec<PackageResult>, String> {
+    let mut rpm = command("rpm");
+    rpm.arg("--query").arg("--queryformat").arg(RPM_FORMAT);
+    match package_name {
+        Some(package_name) => rpm.arg(package_name),
+        None => rpm.arg("--all"),
+    };
+    let output = match rpm.output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[rpm] {}", e)),
+    };
+    // "package x is not installed" is printed to stdout with a failing status
+    if !output.status.success() {
+        return Ok(vec![]);
+    }
+
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        // bash	0	5.2.26	3.fc40	The GNU Bourne Again shell
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [name, epoch, version, release, summary] = columns[..] else {
+            warn!("[rpm] skipping line {line:?}");
+            continue;
+        };
+        installed.push(PackageResult::installed(
+            "dnf",
+            name,
+            &full_version(epoch, version, release),
+            "",
+            summary,
+            "",
+        ));
+    }
+
+    Ok(installed)
+}
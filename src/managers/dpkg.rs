@@ -0,0 +1,448 @@
+//! Reads the dpkg and apt state files directly, which is a lot faster than
+//! asking `apt` and doesn't depend on its unstable CLI output.
+//!
+//! This is only a fast path: it reads uncompressed `Packages` lists and knows
+//! the default priorities, the rest (compressed lists, pins in
+//! `/etc/apt/preferences`, a default release) is left to `apt`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::version::compare_debian;
+
+/// The installed packages, one stanza per package and architecture.
+pub const STATUS: &str = "/var/lib/dpkg/status";
+/// The `Packages` index of every configured source, downloaded by `apt update`.
+const LISTS: &str = "/var/lib/apt/lists";
+/// Which packages were installed as a dependency.
+const EXTENDED_STATES: &str = "/var/lib/apt/extended_states";
+/// Pins that change which version apt picks.
+const PREFERENCES: &str = "/etc/apt/preferences";
+const PREFERENCES_DIR: &str = "/etc/apt/preferences.d";
+/// The apt config, `APT::Default-Release` changes the priorities as well.
+const APT_CONF: &str = "/etc/apt/apt.conf";
+const APT_CONF_DIR: &str = "/etc/apt/apt.conf.d";
+
+/// The priority of a normal source.
+const DEFAULT_PRIORITY: u32 = 500;
+/// The priority of a `NotAutomatic` source that has `ButAutomaticUpgrades`, like backports.
+const UPGRADES_PRIORITY: u32 = 100;
+/// The priority of a `NotAutomatic` source, like experimental.
+const NOT_AUTOMATIC_PRIORITY: u32 = 1;
+
+/// The fields of a stanza boss cares about.
+#[derive(Debug, Default, Clone)]
+pub struct Stanza {
+    pub package: String,
+    pub version: String,
+    /// e.g. `install ok installed`, only set in the status file
+    pub status: String,
+    /// e.g. `amd64` or `all`
+    pub architecture: String,
+    /// only the first line, the synopsis
+    pub description: String,
+    /// `Auto-Installed: 1` in the extended states
+    pub auto_installed: bool,
+}
+
+impl Stanza {
+    /// Returns true if the package is fully installed, not just configured or removed.
+    pub fn is_installed(&self) -> bool {
+        self.status.ends_with(" installed")
+    }
+
+    /// Returns true if the stanza is for the given architecture or for all of them.
+    pub fn is_for(&self, architecture: &str) -> bool {
+        self.architecture == architecture || self.architecture == "all"
+    }
+}
+
+/// Parses a stanza of a deb822 file like the status file or a `Packages` index.
+pub fn parse_stanza(text: &str) -> Stanza {
+    let mut stanza = Stanza::default();
+    for line in text.lines() {
+        // continuation lines of multiline fields start with a space
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key {
+            "Package" => stanza.package = value,
+            "Version" => stanza.version = value,
+            "Status" => stanza.status = value,
+            "Architecture" => stanza.architecture = value,
+            "Description" => stanza.description = value,
+            "Auto-Installed" => stanza.auto_installed = value == "1",
+            _ => {}
+        }
+    }
+    stanza
+}
+
+/// Splits a deb822 file into its stanzas.
+fn stanzas(text: &str) -> impl Iterator<Item = &str> {
+    text.split("\n\n")
+        .filter(|stanza| !stanza.trim().is_empty())
+}
+
+/// Returns the stanzas of a file that belong to the given package, found by
+/// searching for the `Package:` line instead of parsing every stanza.
+fn find_stanzas<'a>(text: &'a str, package_name: &str) -> Vec<&'a str> {
+    let needle = format!("Package: {}\n", package_name);
+    let mut found = vec![];
+    for (index, _) in text.match_indices(&needle) {
+        if index != 0 && !text[..index].ends_with('\n') {
+            continue;
+        }
+        let end = text[index..]
+            .find("\n\n")
+            .map(|end| index + end)
+            .unwrap_or(text.len());
+        found.push(&text[index..end]);
+    }
+    found
+}
+
+/// Reads the status file, `None` if this is not a dpkg based system.
+fn read_status() -> io::Result<Option<String>> {
+    match fs::read_to_string(STATUS) {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the names of all packages that apt installed as a dependency.
+fn auto_installed() -> Vec<String> {
+    let text = fs::read_to_string(EXTENDED_STATES).unwrap_or_default();
+    stanzas(&text)
+        .map(parse_stanza)
+        .filter(|stanza| stanza.auto_installed)
+        .map(|stanza| stanza.package)
+        .collect()
+}
+
+/// Returns all installed packages, `None` if there is no status file.
+pub fn installed() -> io::Result<Option<Vec<Stanza>>> {
+    let Some(text) = read_status()? else {
+        return Ok(None);
+    };
+    let auto_installed = auto_installed();
+
+    let mut installed: Vec<Stanza> = vec![];
+    for mut stanza in stanzas(&text).map(parse_stanza) {
+        // packages of several architectures show up once per architecture
+        if !stanza.is_installed() || installed.iter().any(|i| i.package == stanza.package) {
+            continue;
+        }
+        stanza.auto_installed = auto_installed.contains(&stanza.package);
+        installed.push(stanza);
+    }
+    Ok(Some(installed))
+}
+
+/// Returns the installed package with the given name, `Ok(None)` if it's not
+/// installed and an error if the status file can't be read at all.
+pub fn installed_package(package_name: &str, architecture: &str) -> io::Result<Option<Stanza>> {
+    let Some(text) = read_status()? else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, STATUS));
+    };
+    let Some(mut stanza) = installed_stanza(&text, package_name, architecture) else {
+        return Ok(None);
+    };
+    stanza.auto_installed = auto_installed().contains(&stanza.package);
+    Ok(Some(stanza))
+}
+
+/// Returns the installed stanza of a package in the status file. With multi-arch
+/// a package can be installed for several architectures, like `libc6:amd64` and
+/// `libc6:i386`, then the native one wins like it does for the candidate.
+fn installed_stanza(text: &str, package_name: &str, architecture: &str) -> Option<Stanza> {
+    find_stanzas(text, package_name)
+        .into_iter()
+        .map(parse_stanza)
+        .filter(Stanza::is_installed)
+        .min_by_key(|stanza| !stanza.is_for(architecture))
+}
+
+/// Returns the uncompressed `Packages` indexes, empty if apt keeps them compressed
+/// (`Acquire::GzipIndexes`) or `apt update` never ran.
+pub fn package_lists() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(LISTS) else {
+        return vec![];
+    };
+    let mut lists: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().ends_with("_Packages"))
+        })
+        .collect();
+    lists.sort();
+    lists
+}
+
+/// Turns a list file name into a short repo name like `noble-updates/main`,
+/// `deb.debian.org_debian_dists_bookworm_main_binary-amd64_Packages` is `bookworm/main`.
+pub fn repo_name(list: &Path) -> String {
+    let name = list
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match name.split_once("_dists_") {
+        Some((_, rest)) => {
+            let parts: Vec<&str> = rest.split('_').collect();
+            match parts[..] {
+                [suite, component, ..] if !component.starts_with("binary-") => {
+                    format!("{}/{}", suite, component)
+                }
+                [suite, ..] => suite.to_string(),
+                [] => name,
+            }
+        }
+        // flat repositories have no dists, the host is all there is
+        None => name.split('_').next().unwrap_or_default().to_string(),
+    }
+}
+
+/// Returns the paths of the files in a directory, nothing if it doesn't exist.
+fn dir_files(dir: &str) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+/// Returns true if pins or a default release change which version apt picks,
+/// those are only understood by apt itself.
+pub fn has_pins() -> bool {
+    let has_preferences = std::iter::once(PathBuf::from(PREFERENCES))
+        .chain(dir_files(PREFERENCES_DIR))
+        .any(|path| {
+            fs::read_to_string(path).is_ok_and(|text| {
+                text.lines()
+                    .any(|line| line.trim_start().starts_with("Pin-Priority:"))
+            })
+        });
+    let has_default_release = std::iter::once(PathBuf::from(APT_CONF))
+        .chain(dir_files(APT_CONF_DIR))
+        .any(|path| fs::read_to_string(path).is_ok_and(|text| text.contains("Default-Release")));
+    has_preferences || has_default_release
+}
+
+/// Returns the architecture dpkg installs packages for, like `amd64`.
+pub fn native_architecture() -> Option<String> {
+    let output = Command::new("dpkg")
+        .arg("--print-architecture")
+        .output()
+        .ok()?;
+    let architecture = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!architecture.is_empty()).then_some(architecture)
+}
+
+/// Returns the priority of the source a list belongs to, from the `NotAutomatic`
+/// and `ButAutomaticUpgrades` fields of its `Release` file.
+fn priority(list: &Path) -> u32 {
+    let list_name = list
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // the release file of a list shares the start of its name, like
+    // `..._dists_bookworm-backports_InRelease` for `..._dists_bookworm-backports_main_binary-amd64_Packages`
+    let release = dir_files(LISTS)
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let prefix = name
+                .strip_suffix("InRelease")
+                .or_else(|| name.strip_suffix("Release"))?
+                .to_string();
+            list_name
+                .starts_with(&prefix)
+                .then_some((prefix.len(), path))
+        })
+        .max_by_key(|(length, _)| *length);
+    let Some((_, release)) = release else {
+        return DEFAULT_PRIORITY;
+    };
+
+    let text = fs::read_to_string(release).unwrap_or_default();
+    let is_set = |field: &str| {
+        text.lines().any(|line| {
+            line.strip_prefix(field)
+                .is_some_and(|value| value.trim() == "yes")
+        })
+    };
+    match (is_set("NotAutomatic:"), is_set("ButAutomaticUpgrades:")) {
+        (true, true) => UPGRADES_PRIORITY,
+        (true, false) => NOT_AUTOMATIC_PRIORITY,
+        _ => DEFAULT_PRIORITY,
+    }
+}
+
+/// Returns the version apt would pick for a package with the repo it's from,
+/// and whether any package provides it (a virtual package).
+///
+/// Only stanzas of the native architecture (or `all`) count. The newest version
+/// of the sources with the highest priority wins, but an installed package is
+/// never upgraded from a `NotAutomatic` source like experimental.
+pub fn candidate(
+    lists: &[PathBuf],
+    package_name: &str,
+    architecture: &str,
+    is_installed: bool,
+) -> (Option<(Stanza, String)>, bool) {
+    let mut best: Option<(u32, Stanza, String)> = None;
+    let mut provided = false;
+    for list in lists {
+        let text = match fs::read_to_string(list) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("[apt] could not read {}: {}", list.display(), e);
+                continue;
+            }
+        };
+        let mut priority = None;
+        for stanza in find_stanzas(&text, package_name)
+            .into_iter()
+            .map(parse_stanza)
+            .filter(|stanza| stanza.is_for(architecture))
+        {
+            let priority = *priority.get_or_insert_with(|| self::priority(list));
+            if is_installed && priority == NOT_AUTOMATIC_PRIORITY {
+                continue;
+            }
+            let better = match &best {
+                Some((best_priority, current, _)) => {
+                    priority > *best_priority
+                        || (priority == *best_priority
+                            && compare_debian(&stanza.version, &current.version).is_gt())
+                }
+                None => true,
+            };
+            if better {
+                best = Some((priority, stanza, repo_name(list)));
+            }
+        }
+        // only look for providers as long as the real package wasn't found
+        if best.is_none() && !provided {
+            provided = text
+                .lines()
+                .filter_map(|line| line.strip_prefix("Provides:"))
+                .any(|line| provides(line, package_name));
+        }
+    }
+    (best.map(|(_, stanza, repo)| (stanza, repo)), provided)
+}
+
+/// Returns true if a `Provides` field like `awk, mawk (= 1.3)` has the given package.
+fn provides(field: &str, package_name: &str) -> bool {
+    field
+        .split(',')
+        .any(|provided| provided.split_whitespace().next() == Some(package_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = "\
+Package: libc6
+Status: install ok installed
+Architecture: i386
+Version: 2.36-9
+Description: GNU C Library: Shared libraries
+ Contains the standard libraries that are used by nearly all programs on
+ Package: not-a-package
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+Version: 2.36-9+deb12u4
+Description: GNU C Library: Shared libraries
+
+Package: mawk
+Status: deinstall ok config-files
+Architecture: amd64
+Version: 1.3.4.20200120-3.1
+Provides: awk
+Description: Pattern scanning and text processing language
+
+Package: foo-libc6
+Status: install ok installed
+Architecture: all
+Version: 1.0
+";
+
+    #[test]
+    fn stanza() {
+        let stanza = parse_stanza(stanzas(STATUS).next().unwrap());
+        assert_eq!(stanza.package, "libc6");
+        assert_eq!(stanza.version, "2.36-9");
+        assert_eq!(stanza.status, "install ok installed");
+        assert_eq!(stanza.architecture, "i386");
+        // continuation lines are skipped, not read as fields
+        assert_eq!(stanza.description, "GNU C Library: Shared libraries");
+        assert!(stanza.is_installed());
+        assert!(!parse_stanza(stanzas(STATUS).nth(2).unwrap()).is_installed());
+    }
+
+    #[test]
+    fn stanzas_of_package() {
+        let found = find_stanzas(STATUS, "libc6");
+        assert_eq!(found.len(), 2);
+        assert!(found
+            .iter()
+            .all(|stanza| stanza.starts_with("Package: libc6\n")));
+        assert!(found[1].ends_with("Description: GNU C Library: Shared libraries"));
+        // neither inside a continuation line nor a suffix of another name
+        assert!(find_stanzas(STATUS, "not-a-package").is_empty());
+        assert_eq!(find_stanzas(STATUS, "foo-libc6").len(), 1);
+        assert!(find_stanzas(STATUS, "awk").is_empty());
+    }
+
+    #[test]
+    fn installed_architecture() {
+        let native = installed_stanza(STATUS, "libc6", "amd64").unwrap();
+        assert_eq!(native.version, "2.36-9+deb12u4");
+        // a foreign architecture is still installed without the native one
+        let foreign = installed_stanza(STATUS, "libc6", "arm64").unwrap();
+        assert_eq!(foreign.architecture, "i386");
+        assert!(installed_stanza(STATUS, "foo-libc6", "amd64").is_some());
+        assert!(installed_stanza(STATUS, "mawk", "amd64").is_none());
+    }
+
+    #[test]
+    fn repo() {
+        let repo = |name: &str| repo_name(Path::new(LISTS).join(name).as_path());
+        assert_eq!(
+            repo("deb.debian.org_debian_dists_bookworm_main_binary-amd64_Packages"),
+            "bookworm/main"
+        );
+        assert_eq!(
+            repo("archive.ubuntu.com_ubuntu_dists_noble-updates_universe_binary-amd64_Packages"),
+            "noble-updates/universe"
+        );
+        assert_eq!(
+            repo("ppa.example.com_dists_stable_binary-amd64_Packages"),
+            "stable"
+        );
+        assert_eq!(
+            repo("download.example.com_._Packages"),
+            "download.example.com"
+        );
+    }
+
+    #[test]
+    fn virtual_package() {
+        assert!(provides(" awk, mawk (= 1.3)", "awk"));
+        assert!(provides(" awk, mawk (= 1.3)", "mawk"));
+        assert!(!provides(" awk, mawk (= 1.3)", "aw"));
+        assert!(!provides(" gawk", "awk"));
+    }
+}
@@ -0,0 +1,202 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::{check_output, PackageResult};
+use std::path::PathBuf;
+
+/// The `flatpak` package manager, checking all configured remotes.
+pub struct Flatpak;
+
+impl PackageManager for Flatpak {
+    fn name(&self) -> &'static str {
+        "flatpak"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_flatpak(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_flatpak()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/var/lib/flatpak/app"))
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        let mut args = vec!["install"];
+        // the remote is known for search results, flatpak asks otherwise
+        if !result.repo.is_empty() {
+            args.push(&result.repo);
+        }
+        args.push(&result.package);
+        Some(command_line("flatpak", &args))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "flatpak",
+            &["update", installation_flag(result), &result.package],
+        ))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "flatpak",
+            &["uninstall", installation_flag(result), &result.package],
+        ))
+    }
+}
+
+/// Returns the flag that selects the installation of an installed app.
+fn installation_flag(result: &PackageResult) -> &'static str {
+    match result.location.as_deref() {
+        Some("user") => "--user",
+        _ => "--system",
+    }
+}
+
+/// Checks if an app matches the name, either by app ID, by the last part of
+/// the app ID (`org.gimp.GIMP` for `gimp`) or by its display name.
+fn matches_name(app_id: &str, name: &str, package_name: &str) -> bool {
+    app_id.eq_ignore_ascii_case(package_name)
+        || name.eq_ignore_ascii_case(package_name)
+        || app_id
+            .rsplit('.')
+            .next()
+            .is_some_and(|last| last.eq_ignore_ascii_case(package_name))
+}
+
+/// Checks if a package is available or installed using the `flatpak` package manager.
+pub fn check_flatpak(package_name: &str) -> Result<PackageResult, String> {
+    // -----------------------------------
+    // 1. check remotes if package exists
+    // -----------------------------------
+    let output = match command("flatpak")
+        .arg("search")
+        .arg("--columns=application,name,version,remotes,description")
+        .arg(package_name)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[flatpak] {}", e)),
+    };
+
+    // "No matches found" goes to stdout as well
+    let lines = check_output(output).unwrap_or_default();
+
+    // ------------------------------------------------------
+    // 2. get info about package: newest version, description
+    // ------------------------------------------------------
+    // org.gimp.GIMP	GNU Image Manipulation Program	2.10.38	flathub	Create images and edit photographs
+    let mut found: Option<(String, String, String, String)> = None;
+    for line in &lines {
+        let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let [app_id, name, version, remotes, desc] = columns[..] else {
+            debug!("[flatpak] skipping line {line:?}");
+            continue;
+        };
+        if !matches_name(app_id, name, package_name) {
+            continue;
+        }
+        // several remotes are separated by commas, the first one is used
+        let remote = remotes.split(',').next().unwrap_or_default();
+        let entry = (
+            app_id.to_string(),
+            version.to_string(),
+            remote.to_string(),
+            desc.to_string(),
+        );
+        // an exact app ID wins over a match by name
+        if app_id.eq_ignore_ascii_case(package_name) {
+            found = Some(entry);
+            break;
+        }
+        found.get_or_insert(entry);
+    }
+
+    // --------------------------------
+    // 3. check if package is installed
+    // --------------------------------
+    let installed = installed_apps()?
+        .into_iter()
+        .find(|(installed, name)| match &found {
+            Some((app_id, _, _, _)) => *app_id == installed.package,
+            None => matches_name(&installed.package, name, package_name),
+        })
+        .map(|(installed, _)| installed);
+
+    match (found, installed) {
+        (Some((app_id, version, _, desc)), Some(installed)) => {
+            let local_version = installed.installed_version.unwrap_or_default();
+            let result = PackageResult::installed(
+                "flatpak",
+                &app_id,
+                &local_version,
+                &version,
+                &desc,
+                &installed.repo,
+            );
+            Ok(match installed.location {
+                Some(location) => result.with_location(&location),
+                None => result,
+            })
+        }
+        // installed from a remote that is gone or doesn't list it anymore
+        (None, Some(installed)) => Ok(installed),
+        (Some((app_id, version, remote, desc)), None) => Ok(PackageResult::available(
+            "flatpak", &app_id, &version, &desc, &remote,
+        )),
+        (None, None) => Ok(PackageResult::none("flatpak", package_name)),
+    }
+}
+
+/// Lists all apps installed with `flatpak`, for the user and the system.
+pub fn list_flatpak() -> Result<Vec<PackageResult>, String> {
+    Ok(installed_apps()?
+        .into_iter()
+        .map(|(installed, _)| installed)
+        .collect())
+}
+
+/// Lists all installed apps with their display names, matching by name needs them.
+fn installed_apps() -> Result<Vec<(PackageResult, String)>, String> {
+    let output = match command("flatpak")
+        .arg("list")
+        .arg("--app")
+        .arg("--columns=application,name,version,origin,installation")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[flatpak] {}", e)),
+    };
+
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        // org.gimp.GIMP	GNU Image Manipulation Program	2.10.38	flathub	system
+        let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let [app_id, name, version, origin, installation] = columns[..] else {
+            warn!("[flatpak] skipping line {line:?}");
+            continue;
+        };
+        installed.push((
+            PackageResult::installed("flatpak", app_id, version, "", "", origin)
+                .with_location(installation),
+            name.to_string(),
+        ));
+    }
+
+    Ok(installed)
+}
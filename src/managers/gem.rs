@@ -0,0 +1,151 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, config, PackageResult};
+
+/// RubyGems, the `gem` command.
+pub struct Gem;
+
+impl PackageManager for Gem {
+    fn name(&self) -> &'static str {
+        "gem"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_gem(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_gem()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Gem
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_source(&["install", &result.package]))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_source(&["update", &result.package]))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("gem", &["uninstall", &result.package]))
+    }
+}
+
+/// Builds a `gem` command line that only uses the configured source, if any.
+fn with_source(args: &[&str]) -> Vec<String> {
+    let source = config::get().manager("gem").source;
+    let mut args = args.to_vec();
+    if let Some(source) = &source {
+        args.extend(["--clear-sources", "--source", source]);
+    }
+    command_line("gem", &args)
+}
+
+/// Parses a line of `gem list` or `gem search`, like
+/// `bundler (default: 2.5.11, 2.4.0)`, into the name and the newest version.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let (name, versions) = line.split_once(" (")?;
+    let newest = versions
+        .trim_end_matches(')')
+        .split(", ")
+        .next()?
+        .trim_start_matches("default: ");
+    // platform gems look like `1.16.7 x86_64-linux`
+    let newest = newest.split_whitespace().next()?;
+    Some((name, newest))
+}
+
+/// Checks if a package is available or installed using `gem`.
+pub fn check_gem(package_name: &str) -> Result<PackageResult, String> {
+    // -------------------------------------
+    // 1. check the source if the gem exists
+    // -------------------------------------
+    let mut search = command("gem");
+    search
+        .arg("search")
+        .arg("--remote")
+        .arg("--exact")
+        .arg(package_name);
+    if let Some(source) = config::get().manager("gem").source {
+        search.arg("--clear-sources").arg("--source").arg(source);
+    }
+    let output = match search.output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[gem] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // *** REMOTE GEMS ***
+    //
+    // rails (7.2.1)
+    let latest = lines
+        .iter()
+        .filter_map(|line| parse_line(line))
+        .find(|(name, _)| *name == package_name)
+        .map(|(_, version)| version.to_string());
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = list_gem()?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (latest, installed) {
+        (Some(latest), Some(installed)) => Ok(PackageResult::installed(
+            "gem",
+            package_name,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &latest,
+            "",
+            "",
+        )),
+        (None, Some(installed)) => Ok(installed),
+        (Some(latest), None) => Ok(PackageResult::available(
+            "gem",
+            package_name,
+            &latest,
+            "",
+            "",
+        )),
+        (None, None) => Ok(PackageResult::none("gem", package_name)),
+    }
+}
+
+/// Lists all gems installed with `gem`, with their newest installed version.
+pub fn list_gem() -> Result<Vec<PackageResult>, String> {
+    let output = match command("gem").arg("list").arg("--local").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[gem] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        if line.starts_with("***") {
+            continue;
+        }
+        let Some((name, version)) = parse_line(line) else {
+            warn!("[gem] skipping line {line:?}");
+            continue;
+        };
+        installed.push(PackageResult::installed("gem", name, version, "", "", ""));
+    }
+
+    Ok(installed)
+}
@@ -1,50 +1,210 @@
-use crate::{check_output, reduce_whitespace, PackageResult};
-use std::process::Command;
-use strp::*;
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::{check_output, config, PackageResult};
+use serde::Deserialize;
+use std::path::PathBuf;
 
-/// Checks if a package is available or installed using the `go` package manager.
-/// disabled until go package check is implemented
+/// The `go` toolchain, checked through the binaries it installed.
+pub struct Go;
+
+impl PackageManager for Go {
+    fn name(&self) -> &'static str {
+        "go"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_go(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_go()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: false,
+            update: true,
+            remove: false,
+            search: false,
+        }
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        let line = command_line("go", &["install", &format!("{}@latest", result.package)]);
+        // download from the same proxy the latest version was looked up in
+        Some(match proxy() {
+            Some(proxy) => ["env".to_string(), format!("GOPROXY={}", proxy)]
+                .into_iter()
+                .chain(line)
+                .collect(),
+            None => line,
+        })
+    }
+}
+
+/// The output of `go list -m -json`.
+#[derive(Deserialize)]
+struct Module {
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+/// Returns the directory `go install` puts binaries in, `GOBIN` or the `bin` of
+/// the first `GOPATH` entry.
+fn bin_dir() -> Result<PathBuf, String> {
+    // go env also knows the values set with `go env -w` and the defaults
+    let output = match command("go").arg("env").arg("GOBIN").arg("GOPATH").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[go] {}", e)),
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let gobin = lines.next().unwrap_or_default().trim();
+    let gopath = lines.next().unwrap_or_default().trim();
+
+    if !gobin.is_empty() {
+        return Ok(PathBuf::from(gobin));
+    }
+    match std::env::split_paths(gopath).next() {
+        Some(gopath) if !gopath.as_os_str().is_empty() => Ok(gopath.join("bin")),
+        _ => Err("[go] neither GOBIN nor GOPATH is set".to_string()),
+    }
+}
+
+/// The configured proxy, `None` to use go's own `GOPROXY` (proxy.golang.org by default).
+fn proxy() -> Option<String> {
+    config::get().manager("go").source
+}
+
+/// Looks up the latest version of a module through the proxy,
+/// `None` if the module can't be found.
+fn latest_version(module: &str) -> Option<String> {
+    let mut list = command("go");
+    list.arg("list")
+        .arg("-m")
+        .arg("-json")
+        .arg(format!("{}@latest", module));
+    // go handles https and file:// proxies by itself
+    if let Some(proxy) = proxy() {
+        list.env("GOPROXY", proxy);
+    }
+    let output = match list.output() {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("[go] {}", e);
+            return None;
+        }
+    };
+    if !output.status.success() {
+        debug!("[go] {}", String::from_utf8_lossy(&output.stderr).trim());
+        return None;
+    }
+    match serde_json::from_slice::<Module>(&output.stdout) {
+        Ok(module) => Some(module.version),
+        Err(e) => {
+            warn!("[go] {}", e);
+            None
+        }
+    }
+}
+
+/// Checks if a package is installed using the `go` package manager.
+/// go has no search feature, so only installed binaries are found
 pub fn check_go(package_name: &str) -> Result<PackageResult, String> {
-    // TODO: implement go package check
-    let output = Command::new("go")
+    // -------------------------------------------------------
+    // 1. find the binary by its name or the full package path
+    // -------------------------------------------------------
+    let found = list_go()?.into_iter().find(|installed| {
+        let name = installed.package.rsplit('/').next().unwrap_or_default();
+        let binary = installed
+            .location
+            .as_deref()
+            .and_then(|location| location.rsplit('/').next())
+            .unwrap_or_default();
+        package_name == name || package_name == binary || package_name == installed.package
+    });
+    let Some(installed) = found else {
+        return Ok(PackageResult::none("go", package_name));
+    };
+
+    // ---------------------------------------------------------
+    // 2. get the latest version of its module through the proxy
+    // ---------------------------------------------------------
+    // binaries built from a local checkout have no version to compare
+    let local_version = installed.installed_version.clone().unwrap_or_default();
+    if local_version.is_empty() || local_version == "(devel)" {
+        return Ok(installed);
+    }
+    // every check asks the proxy, so it can be turned off
+    if config::get().manager("go").check_latest == Some(false) {
+        return Ok(installed);
+    }
+    match latest_version(&installed.repo) {
+        Some(latest) => Ok(PackageResult::installed(
+            "go",
+            &installed.package,
+            &local_version,
+            &latest,
+            "",
+            &installed.repo,
+        )
+        .with_location(installed.location.as_deref().unwrap_or_default())),
+        None => Ok(installed),
+    }
+}
+
+/// Lists all binaries installed with `go install`.
+pub fn list_go() -> Result<Vec<PackageResult>, String> {
+    let bin_dir = bin_dir()?;
+    if !bin_dir.is_dir() {
+        debug!("[go] {} does not exist", bin_dir.display());
+        return Ok(vec![]);
+    }
+
+    let output = match command("go")
         .arg("version")
         .arg("-m")
-        .arg("/home/noah/go/bin")
+        .arg(&bin_dir)
         .output()
-        .expect("CUSTOM ERROR: failed to execute go list -m -u <package_name>");
-
-    if !output.stdout.is_empty() {
-        let stdout: Vec<u8> = output.stdout;
-        let stdout_string = String::from_utf8(stdout).unwrap();
-
-        let filtered_lines = stdout_string
-            .split('\n')
-            .filter(|line| line.contains("path") && !line.is_empty())
-            .collect::<Vec<_>>();
-
-        for line in &filtered_lines {
-            let mut chunks = line.split_whitespace();
-            chunks.next();
-            let fullname = chunks.next().expect("CUSTOM ERROR: failed to get fullname");
-            let mut fullnamesplit = fullname.split('/');
-            fullnamesplit.next();
-            let name = fullnamesplit
-                .clone()
-                .last()
-                .expect("CUSTOM ERROR: failed to get name");
-            let repo = fullnamesplit.collect::<Vec<_>>().join("/");
-
-            if package_name == name {
-                return Result::Ok(PackageResult::some(
-                    "go",
-                    fullname,
-                    "installed",
-                    "",
-                    "",
-                    repo.as_str(),
-                ));
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[go] {}", e)),
+    };
+
+    let lines = check_output(output).unwrap_or_default();
+
+    // /home/user/go/bin/gopls: go1.22.0
+    // 	path	golang.org/x/tools/gopls
+    // 	mod	golang.org/x/tools/gopls	v0.15.0	h1:...
+    // 	dep	...
+    let mut installed = vec![];
+    let mut binary: Option<&str> = None;
+    let mut path: Option<&str> = None;
+    for line in &lines {
+        if !line.starts_with(char::is_whitespace) {
+            binary = line.rsplit_once(": ").map(|(binary, _)| binary);
+            path = None;
+            continue;
+        }
+        let mut chunks = line.split_whitespace();
+        match chunks.next() {
+            Some("path") => path = chunks.next(),
+            Some("mod") => {
+                let (Some(binary), Some(path)) = (binary, path) else {
+                    warn!("[go] skipping module without binary or path: {line:?}");
+                    continue;
+                };
+                let module = chunks.next().unwrap_or(path);
+                let version = chunks.next().unwrap_or_default();
+                installed.push(
+                    PackageResult::installed("go", path, version, "", "", module)
+                        .with_location(binary),
+                );
             }
+            _ => {}
         }
     }
-    Result::Ok(PackageResult::none("go", package_name))
+
+    Ok(installed)
 }
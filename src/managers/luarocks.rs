@@ -0,0 +1,144 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, config, PackageResult};
+
+/// LuaRocks, read through its `--porcelain` output.
+pub struct Luarocks;
+
+impl PackageManager for Luarocks {
+    fn name(&self) -> &'static str {
+        "luarocks"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_luarocks(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_luarocks()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Luarocks
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_server(&["install", &result.package]))
+    }
+
+    // installing again gets the newest version, there is no upgrade command
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(with_server(&["install", &result.package]))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("luarocks", &["remove", &result.package]))
+    }
+}
+
+/// Builds a `luarocks` command line that only uses the configured server, if any.
+fn with_server(args: &[&str]) -> Vec<String> {
+    let source = config::get().manager("luarocks").source;
+    let mut args = args.to_vec();
+    if let Some(source) = &source {
+        args.extend(["--only-server", source]);
+    }
+    command_line("luarocks", &args)
+}
+
+/// Checks if a rock is available or installed using `luarocks`.
+pub fn check_luarocks(package_name: &str) -> Result<PackageResult, String> {
+    // ---------------------------------------
+    // 1. check the servers if the rock exists
+    // ---------------------------------------
+    let mut search = command("luarocks");
+    search.arg("search").arg("--porcelain").arg(package_name);
+    if let Some(source) = config::get().manager("luarocks").source {
+        search.arg("--only-server").arg(source);
+    }
+    let output = match search.output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[luarocks] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // luasocket	3.1.0-1	rockspec	https://luarocks.org
+    // luasocket	3.1.0-1	src	https://luarocks.org
+    // luasocket	3.0.0-1	rockspec	https://luarocks.org
+    // versions are listed newest first
+    let mut found: Option<(String, String)> = None;
+    for line in &lines {
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [name, version, _kind, server] = columns[..] else {
+            debug!("[luarocks] skipping line {line:?}");
+            continue;
+        };
+        if name == package_name {
+            found = Some((version.to_string(), server.to_string()));
+            break;
+        }
+    }
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = list_luarocks()?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (found, installed) {
+        (Some((version, server)), Some(installed)) => Ok(PackageResult::installed(
+            "luarocks",
+            package_name,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &version,
+            "",
+            &server,
+        )
+        .with_location(installed.location.as_deref().unwrap_or_default())),
+        (None, Some(installed)) => Ok(installed),
+        (Some((version, server)), None) => Ok(PackageResult::available(
+            "luarocks",
+            package_name,
+            &version,
+            "",
+            &server,
+        )),
+        (None, None) => Ok(PackageResult::none("luarocks", package_name)),
+    }
+}
+
+/// Lists all rocks installed with `luarocks`, in every tree it knows.
+pub fn list_luarocks() -> Result<Vec<PackageResult>, String> {
+    let output = match command("luarocks").arg("list").arg("--porcelain").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[luarocks] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        // luasocket	3.1.0-1	installed	/usr/local/lib/luarocks/rocks-5.4
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [name, version, _status, tree] = columns[..] else {
+            warn!("[luarocks] skipping line {line:?}");
+            continue;
+        };
+        installed.push(
+            PackageResult::installed("luarocks", name, version, "", "", "").with_location(tree),
+        );
+    }
+
+    Ok(installed)
+}
@@ -1,13 +1,409 @@
+pub mod apk;
 pub mod apt;
+pub mod brew;
 pub mod cargo;
+pub mod composer;
+pub mod cpan;
+pub mod dnf;
+mod dpkg;
+pub mod flatpak;
+pub mod gem;
 pub mod go;
+pub mod luarocks;
 pub mod nix;
+pub mod node;
+pub mod pacman;
+pub mod portage;
+pub mod python;
 pub mod snap;
-pub mod yay;
-
-pub use apt::check_apt;
-pub use cargo::check_cargo;
-// pub use go::check_go;
-pub use nix::check_nix;
-pub use snap::check_snap;
-pub use yay::check_yay;
+pub mod xbps;
+pub mod zypper;
+
+pub use apk::Apk;
+pub use apt::Apt;
+pub use brew::Brew;
+pub use cargo::Cargo;
+pub use composer::Composer;
+pub use cpan::Cpan;
+pub use dnf::Dnf;
+pub use flatpak::Flatpak;
+pub use gem::Gem;
+pub use go::Go;
+pub use luarocks::Luarocks;
+pub use nix::Nix;
+pub use node::{Node, BUN, NPM, PNPM, YARN};
+pub use pacman::Pacman;
+pub use portage::Portage;
+pub use python::{Python, PIP, PIPX, UV};
+pub use snap::Snap;
+pub use xbps::Xbps;
+pub use zypper::Zypper;
+
+use crate::cache::Cache;
+use crate::version::Scheme;
+use crate::{config, PackageResult};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Describes which operations a manager backend supports.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Capabilities {
+    /// can look up a single package by exact name
+    pub query: bool,
+    /// can list every package it has installed
+    pub list_installed: bool,
+    /// can install packages
+    pub install: bool,
+    /// can update installed packages
+    pub update: bool,
+    /// can remove installed packages
+    pub remove: bool,
+    /// can search for packages by a part of their name
+    pub search: bool,
+}
+
+/// A package manager backend that boss can detect and query.
+pub trait PackageManager: Send + Sync {
+    /// The name of the manager, also used as the binary to look for.
+    fn name(&self) -> &'static str;
+
+    /// Checks if the manager is available on this system.
+    fn detect(&self) -> bool {
+        which(&binary(self.name()))
+    }
+
+    /// Checks if a package is available or installed.
+    fn query(&self, package_name: &str) -> Result<PackageResult, String>;
+
+    /// Returns all packages installed with this manager.
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String>;
+
+    /// Searches for packages matching the term, nothing if the manager can't search.
+    fn search(&self, _term: &str) -> Result<Vec<PackageResult>, String> {
+        Ok(vec![])
+    }
+
+    /// Returns the operations this manager supports.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Returns how the manager orders its versions.
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Semver
+    }
+
+    /// Returns the file or directory that changes whenever something is
+    /// installed or removed, cached results older than it are stale.
+    fn database(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the command line that installs the package, `None` if the
+    /// manager can't install it.
+    fn install_command(&self, _result: &PackageResult) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Returns the command line that updates the installed package.
+    fn update_command(&self, _result: &PackageResult) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Returns the command line that removes the installed package.
+    fn remove_command(&self, _result: &PackageResult) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// All known managers, in the order they are checked.
+static REGISTRY: &[&dyn PackageManager] = &[
+    &Snap, &Flatpak, &Apt, &Pacman, &Dnf, &Zypper, &Apk, &Xbps, &Portage, &Brew, &Cargo, &Go, &NPM,
+    &PNPM, &YARN, &BUN, &PIP, &PIPX, &UV, &Gem, &Composer, &Cpan, &Luarocks, &Nix,
+];
+
+/// Returns every known manager, installed or not.
+pub fn registry() -> &'static [&'static dyn PackageManager] {
+    REGISTRY
+}
+
+/// Returns the manager with the given name.
+pub fn find(name: &str) -> Option<&'static dyn PackageManager> {
+    REGISTRY
+        .iter()
+        .copied()
+        .find(|manager| manager.name() == name)
+}
+
+/// Returns the managers that are installed on this system and enabled in the
+/// config, ordered by preference.
+pub fn installed_managers() -> Vec<&'static dyn PackageManager> {
+    let config = config::get();
+    for name in config.enabled.iter().chain(&config.order) {
+        if !REGISTRY.iter().any(|manager| manager.name() == name) {
+            warn!("unknown manager in config: {}", name);
+        }
+    }
+
+    let mut managers: Vec<&'static dyn PackageManager> = REGISTRY
+        .iter()
+        .copied()
+        .filter(|manager| config.is_enabled(manager.name()))
+        .filter(|manager| manager.detect())
+        .collect();
+    // stable sort, so unranked managers keep the default order
+    managers.sort_by_key(|manager| config.preference(manager.name()));
+    managers
+}
+
+/// Returns the binary for a tool, the configured path or just its name.
+pub fn binary(tool: &str) -> String {
+    config::get()
+        .manager(tool)
+        .path
+        .unwrap_or_else(|| tool.to_string())
+}
+
+/// Builds the command line for a tool: the binary, the extra arguments from the
+/// config and then `args`.
+pub fn command_line(tool: &str, args: &[&str]) -> Vec<String> {
+    let settings = config::get().manager(tool);
+    let mut line = vec![settings.path.unwrap_or_else(|| tool.to_string())];
+    line.extend(settings.args);
+    line.extend(args.iter().map(|arg| arg.to_string()));
+    line
+}
+
+/// Prefixes a command line with `sudo`, unless we already are root.
+pub fn sudo(mut line: Vec<String>) -> Vec<String> {
+    let is_root = Command::new("id")
+        .arg("-u")
+        .output()
+        .is_ok_and(|output| output.stdout.trim_ascii() == b"0");
+    if !is_root {
+        line.insert(0, "sudo".to_string());
+    }
+    line
+}
+
+/// Creates a command for a tool, using the binary path and extra arguments
+/// from the config.
+pub fn command(tool: &str) -> Command {
+    let settings = config::get().manager(tool);
+    let mut command = Command::new(settings.path.as_deref().unwrap_or(tool));
+    command.args(settings.args);
+    command
+}
+
+/// Returns true if the given binary can be found in the PATH.
+pub fn which(binary: &str) -> bool {
+    Command::new("which")
+        .arg(binary)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// How many jobs run at once, `--similar` easily asks for hundreds.
+const MAX_PARALLEL: usize = 16;
+
+/// Runs `work` for every job in its own thread, at most [`MAX_PARALLEL`] at once,
+/// and waits for each of them until `timeout` after it started.
+///
+/// `on_done` is called for every result as soon as it arrives, jobs that don't
+/// finish in time get the result of `on_timeout` instead. The returned results
+/// keep the order of `jobs`.
+///
+/// Timed out threads are left behind, and so are the processes they started:
+/// they are never killed or reaped, boss just stops waiting for them.
+fn run_all<J, T>(
+    jobs: Vec<J>,
+    timeout: Duration,
+    work: impl Fn(&J) -> T + Clone + Send + 'static,
+    mut on_timeout: impl FnMut(&J) -> T,
+    mut on_done: impl FnMut(&J, &T),
+) -> Vec<T>
+where
+    J: Clone + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let mut results: Vec<Option<T>> = jobs.iter().map(|_| None).collect();
+    // the jobs that were started and are still waited for, with their deadline
+    let mut running: Vec<(usize, Instant)> = vec![];
+    let mut next = 0;
+
+    loop {
+        while running.len() < MAX_PARALLEL && next < jobs.len() {
+            let sender = sender.clone();
+            let job = jobs[next].clone();
+            let work = work.clone();
+            let index = next;
+            thread::spawn(move || {
+                // the receiver is gone if we already timed out
+                let _ = sender.send((index, work(&job)));
+            });
+            running.push((index, Instant::now() + timeout));
+            next += 1;
+        }
+        let Some(deadline) = running.iter().map(|(_, deadline)| *deadline).min() else {
+            break;
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(remaining) {
+            Ok((index, result)) => {
+                // a late result of a job that already timed out is dropped
+                if results[index].is_none() {
+                    running.retain(|(running, _)| *running != index);
+                    on_done(&jobs[index], &result);
+                    results[index] = Some(result);
+                }
+            }
+            Err(_) => {
+                let now = Instant::now();
+                for (index, _) in running.iter().filter(|(_, deadline)| *deadline <= now) {
+                    let result = on_timeout(&jobs[*index]);
+                    on_done(&jobs[*index], &result);
+                    results[*index] = Some(result);
+                }
+                running.retain(|(_, deadline)| *deadline > now);
+            }
+        }
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// Queries all given managers for all given packages in parallel, each check
+/// bounded by `timeout`. Results are taken from and stored in the `cache`.
+///
+/// `on_done` is called for every result as soon as it arrives, checks that
+/// don't finish in time get a timed out result. The returned results are
+/// grouped by package and keep the order of `packages` and `managers`.
+pub fn query_all(
+    managers: &[&'static dyn PackageManager],
+    packages: &[String],
+    cache: &Cache,
+    timeout: Duration,
+    mut on_done: impl FnMut(&PackageResult),
+) -> Vec<PackageResult> {
+    let checks: Vec<(&'static dyn PackageManager, String)> = packages
+        .iter()
+        .flat_map(|package_name| {
+            managers
+                .iter()
+                .map(move |manager| (*manager, package_name.clone()))
+        })
+        .collect();
+
+    let cache = cache.clone();
+    run_all(
+        checks,
+        timeout,
+        move |(manager, package_name)| {
+            match cache.query(*manager, package_name) {
+                Ok(result) => result,
+                Err(e) => PackageResult::error(manager.name(), package_name, &e),
+            }
+            .for_query(package_name)
+        },
+        |(manager, package_name)| {
+            warn!("{} timed out after {:?}", manager.name(), timeout);
+            PackageResult::timed_out(manager.name(), package_name)
+        },
+        |_, result| {
+            debug!("{} finished: {}", result.manager, result.status);
+            on_done(result);
+        },
+    )
+}
+
+/// Searches all given managers for all given terms in parallel, like
+/// [`query_all`]. Every match is a result with the term as its query.
+///
+/// `on_done` is called with the name of each manager as soon as its search
+/// is done. Failed or timed out searches show up as a single result.
+pub fn search_all(
+    managers: &[&'static dyn PackageManager],
+    terms: &[String],
+    cache: &Cache,
+    timeout: Duration,
+    mut on_done: impl FnMut(&str),
+) -> Vec<PackageResult> {
+    let searches: Vec<(&'static dyn PackageManager, String)> = terms
+        .iter()
+        .flat_map(|term| managers.iter().map(move |manager| (*manager, term.clone())))
+        .collect();
+
+    let cache = cache.clone();
+    run_all(
+        searches,
+        timeout,
+        move |(manager, term)| {
+            let found = match cache.search(*manager, term) {
+                Ok(found) => found,
+                Err(e) => vec![PackageResult::error(manager.name(), term, &e)],
+            };
+            found
+                .into_iter()
+                .map(|result| result.for_query(term))
+                .collect::<Vec<_>>()
+        },
+        |(manager, term)| {
+            warn!("{} search timed out after {:?}", manager.name(), timeout);
+            vec![PackageResult::timed_out(manager.name(), term)]
+        },
+        |(manager, _), results| {
+            debug!(
+                "{} search finished: {} results",
+                manager.name(),
+                results.len()
+            );
+            on_done(manager.name());
+        },
+    )
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Lists the installed packages of all given managers in parallel, each
+/// bounded by `timeout`. Installed lists are taken from and stored in the `cache`.
+///
+/// `on_done` is called with the name of each manager as soon as its list is
+/// done. Failed or timed out lists show up as a single result, the results
+/// keep the order of `managers`.
+pub fn list_all(
+    managers: &[&'static dyn PackageManager],
+    cache: &Cache,
+    timeout: Duration,
+    mut on_done: impl FnMut(&str),
+) -> Vec<PackageResult> {
+    let cache = cache.clone();
+    run_all(
+        managers.to_vec(),
+        timeout,
+        move |manager| match cache.list_installed(*manager) {
+            Ok(installed) => installed,
+            Err(e) => vec![PackageResult::error(manager.name(), "", &e)],
+        },
+        |manager| {
+            warn!("{} list timed out after {:?}", manager.name(), timeout);
+            vec![PackageResult::timed_out(manager.name(), "")]
+        },
+        |manager, installed| {
+            debug!(
+                "{} list finished: {} packages",
+                manager.name(),
+                installed.len()
+            );
+            on_done(manager.name());
+        },
+    )
+    .into_iter()
+    .flatten()
+    .collect()
+}
@@ -1,14 +1,66 @@
+use super::{command, command_line, Capabilities, PackageManager};
 use crate::{check_output, reduce_whitespace, PackageResult};
-use std::process::Command;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use strp::*;
 
-/// Checks if a package is available or installed using the `cargo` package manager.
+/// The `nix` package manager, using the `nixpkgs` flake and the user profile.
+pub struct Nix;
+
+impl PackageManager for Nix {
+    fn name(&self) -> &'static str {
+        "nix"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_nix(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_nix()
+    }
+
+    fn search(&self, term: &str) -> Result<Vec<PackageResult>, String> {
+        search_nix(term)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: true,
+        }
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "nix",
+            &["profile", "install", &format!("nixpkgs#{}", result.package)],
+        ))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line(
+            "nix",
+            &["profile", "upgrade", &result.package],
+        ))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(command_line("nix", &["profile", "remove", &result.package]))
+    }
+}
+
+/// Checks if a package is available or installed using the `nix` package manager.
 pub fn check_nix(package_name: &str) -> Result<PackageResult, String> {
     let full_name: String = "nixpkgs#".to_owned().clone() + package_name;
     // -----------------------------------
     // 1. check registry if package exists
     // -----------------------------------
-    let output = match Command::new("nix")
+    let output = match command("nix")
         .arg("search")
         .arg(full_name)
         .arg("^")
@@ -60,22 +112,95 @@ pub fn check_nix(package_name: &str) -> Result<PackageResult, String> {
     // --------------------------------
     // 3. check if package is installed
     // --------------------------------
-    let output = match Command::new("nix").arg("profile").arg("list").output() {
+    for installed in list_nix()? {
+        if package_name == installed.package {
+            // the profile doesn't tell the installed version
+            return Result::Ok(PackageResult::installed(
+                "nix",
+                &installed.package,
+                "",
+                &version,
+                &desc,
+                "",
+            ));
+        }
+    }
+
+    Result::Ok(PackageResult::available(
+        "nix",
+        package_name,
+        &version,
+        &desc,
+        "",
+    ))
+}
+
+/// A package in the output of `nix search --json`.
+#[derive(Deserialize)]
+struct SearchEntry {
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// Searches the names and descriptions in `nixpkgs` using `nix search`.
+pub fn search_nix(term: &str) -> Result<Vec<PackageResult>, String> {
+    let output = match command("nix")
+        .arg("search")
+        .arg("nixpkgs")
+        .arg(term)
+        .arg("--json")
+        .output()
+    {
         Ok(output) => output,
-        Err(e) => return Err(e.to_string()),
+        Err(e) => return Err(format!("[nix] {}", e)),
     };
+    // nothing found is an error without output
+    if output.stdout.is_empty() {
+        return Ok(vec![]);
+    }
+    // {"legacyPackages.x86_64-linux.ripgrep": {"pname": "ripgrep", "version": "14.1.0", ...}}
+    let entries: BTreeMap<String, SearchEntry> = match serde_json::from_slice(&output.stdout) {
+        Ok(entries) => entries,
+        Err(e) => return Err(format!("[nix] parsing error: {}", e)),
+    };
+    let installed = list_nix()?;
 
-    let lines = match check_output(output) {
-        Ok(lines) => lines,
-        Err(e) => return Err(e),
+    let mut found = vec![];
+    for (attr, entry) in &entries {
+        // the name to install is the attribute path without the system
+        let name = match attr.strip_prefix("legacyPackages.") {
+            Some(rest) => rest.split_once('.').map_or(rest, |(_, name)| name),
+            None => attr.as_str(),
+        };
+        let result = if installed.iter().any(|installed| installed.package == name) {
+            PackageResult::installed("nix", name, "", &entry.version, &entry.description, "")
+        } else {
+            PackageResult::available("nix", name, &entry.version, &entry.description, "")
+        };
+        found.push(result);
+    }
+
+    Ok(found)
+}
+
+/// Lists all packages installed in the user's `nix` profile.
+pub fn list_nix() -> Result<Vec<PackageResult>, String> {
+    let output = match command("nix").arg("profile").arg("list").output() {
+        Ok(output) => output,
+        Err(e) => return Err(e.to_string()),
     };
 
+    let lines = check_output(output).unwrap_or_default();
+
     let filtered_lines: Vec<String> = lines
         .iter()
-        .filter(|line| !line.is_empty() && line.contains(package_name))
+        .filter(|line| line.starts_with("Name:"))
         .map(|line| line.to_string())
         .collect();
 
+    let mut installed = vec![];
     for line in &filtered_lines {
         let reduced_line = reduce_whitespace(line.to_string());
         let scanned: Result<String, _> = try_parse!(reduced_line => "Name: {}");
@@ -84,24 +209,8 @@ pub fn check_nix(package_name: &str) -> Result<PackageResult, String> {
             Err(e) => return Err(format!("[nix] parsing error: {e:?}")),
         };
 
-        if package_name == name {
-            return Result::Ok(PackageResult::some(
-                "nix",
-                &name,
-                "installed",
-                &version,
-                &desc,
-                "",
-            ));
-        }
+        installed.push(PackageResult::installed("nix", &name, "", "", "", ""));
     }
 
-    Result::Ok(PackageResult::some(
-        "nix",
-        package_name,
-        "available",
-        &version,
-        &desc,
-        "",
-    ))
+    Ok(installed)
 }
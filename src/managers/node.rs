@@ -0,0 +1,348 @@
+use super::{command, command_line, Capabilities, PackageManager};
+use crate::{check_output, config, PackageResult};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// The javascript package managers, only their global packages are checked.
+/// Versions and descriptions come from the registry through each tool's own
+/// `view` or `info` command, which all read `.npmrc`.
+#[derive(Debug, Clone, Copy)]
+enum Tool {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Npm => "npm",
+            Tool::Pnpm => "pnpm",
+            Tool::Yarn => "yarn",
+            Tool::Bun => "bun",
+        }
+    }
+
+    /// The configured registry, `None` to use the one from `.npmrc`.
+    fn registry(self) -> Option<String> {
+        config::get().manager(self.name()).source
+    }
+
+    /// Builds a command line for a global install, update or removal.
+    fn global_command(self, subcommand: &str, args: &[&str]) -> Vec<String> {
+        let mut line: Vec<&str> = match self {
+            Tool::Yarn => vec!["global", subcommand],
+            Tool::Npm | Tool::Pnpm | Tool::Bun => vec![subcommand, "--global"],
+        };
+        line.extend(args);
+        command_line(self.name(), &line)
+    }
+
+    /// Like [`Tool::global_command`], but downloading from the configured registry.
+    fn download_command(self, subcommand: &str, args: &[&str]) -> Vec<String> {
+        let registry = self.registry();
+        let mut args = args.to_vec();
+        if let Some(registry) = &registry {
+            args.extend(["--registry", registry]);
+        }
+        self.global_command(subcommand, &args)
+    }
+
+    /// Builds the command that looks a package up in the registry.
+    fn view_command(self, package_name: &str) -> Command {
+        let mut view = command(self.name());
+        match self {
+            Tool::Npm | Tool::Pnpm => view
+                .args(["view", "--json", "--fetch-retries=0", package_name])
+                .args(["version", "description"]),
+            Tool::Yarn | Tool::Bun => view.args(["info", "--json", package_name]),
+        };
+        if let Some(registry) = self.registry() {
+            view.arg("--registry").arg(registry);
+        }
+        view
+    }
+}
+
+/// A javascript package manager, one for each [`Tool`].
+pub struct Node(Tool);
+
+/// The `npm` global packages.
+pub const NPM: Node = Node(Tool::Npm);
+/// The `pnpm` global packages.
+pub const PNPM: Node = Node(Tool::Pnpm);
+/// The `yarn global` packages, yarn 1 only since later versions dropped them.
+pub const YARN: Node = Node(Tool::Yarn);
+/// The `bun` global packages.
+pub const BUN: Node = Node(Tool::Bun);
+
+impl PackageManager for Node {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_node(self.0, package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_node(self.0)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        let tool = self.0;
+        Some(match tool {
+            Tool::Npm => tool.download_command("install", &[&result.package]),
+            Tool::Pnpm | Tool::Yarn | Tool::Bun => tool.download_command("add", &[&result.package]),
+        })
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        let tool = self.0;
+        let latest = format!("{}@latest", result.package);
+        Some(match tool {
+            Tool::Npm => tool.download_command("install", &[&latest]),
+            Tool::Pnpm | Tool::Bun => tool.download_command("add", &[&latest]),
+            Tool::Yarn => tool.download_command("upgrade", &[&result.package, "--latest"]),
+        })
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        let tool = self.0;
+        Some(match tool {
+            Tool::Npm => tool.global_command("uninstall", &[&result.package]),
+            Tool::Pnpm | Tool::Yarn | Tool::Bun => {
+                tool.global_command("remove", &[&result.package])
+            }
+        })
+    }
+}
+
+/// The output of `npm view --json <package> version description`, `bun info --json`
+/// prints the whole package with the same fields.
+#[derive(Deserialize)]
+struct View {
+    version: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// The output of `npm ls --json` and every entry of `pnpm ls --json`.
+#[derive(Deserialize)]
+struct Tree {
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Deserialize)]
+struct Dependency {
+    #[serde(default)]
+    version: String,
+}
+
+/// `yarn info --json` wraps the package in a message: `{"type":"inspect","data":{...}}`.
+#[derive(Deserialize)]
+struct Inspect {
+    data: View,
+}
+
+/// Looks up the latest version and description in the registry,
+/// `None` if the package doesn't exist there.
+fn view(tool: Tool, package_name: &str) -> Result<Option<View>, String> {
+    let output = match tool.view_command(package_name).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[{}] {}", tool.name(), e)),
+    };
+    if !output.status.success() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // unknown packages fail with a 404 (`E404` for npm), anything else,
+        // like being offline, is an error and not a missing package
+        if stdout.contains("404") || stderr.contains("404") {
+            debug!("[{}] {}", tool.name(), stderr.trim());
+            return Ok(None);
+        }
+        let message = stderr
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("lookup failed");
+        return Err(format!("[{}] {}", tool.name(), message.trim()));
+    }
+    // nothing is printed if the package exists but has no version
+    if output.stdout.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+
+    let view = match tool {
+        Tool::Yarn => serde_json::from_slice::<Inspect>(&output.stdout).map(|inspect| inspect.data),
+        Tool::Npm | Tool::Pnpm | Tool::Bun => serde_json::from_slice(&output.stdout),
+    };
+    match view {
+        Ok(view) => Ok(Some(view)),
+        Err(e) => Err(format!("[{}] {}", tool.name(), e)),
+    }
+}
+
+/// Checks if a package is available or installed globally with a javascript package manager.
+fn check_node(tool: Tool, package_name: &str) -> Result<PackageResult, String> {
+    // ----------------------------------------
+    // 1. check the registry if package exists
+    // ----------------------------------------
+    let found = view(tool, package_name)?;
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = list_node(tool)?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (found, installed) {
+        (Some(view), Some(installed)) => Ok(PackageResult::installed(
+            tool.name(),
+            package_name,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &view.version,
+            &view.description,
+            "",
+        )),
+        // installed from a tarball, a git repo or a registry that's not configured
+        (None, Some(installed)) => Ok(installed),
+        (Some(view), None) => Ok(PackageResult::available(
+            tool.name(),
+            package_name,
+            &view.version,
+            &view.description,
+            "",
+        )),
+        (None, None) => Ok(PackageResult::none(tool.name(), package_name)),
+    }
+}
+
+/// Lists the globally installed packages of a javascript package manager.
+fn list_node(tool: Tool) -> Result<Vec<PackageResult>, String> {
+    let mut list = command(tool.name());
+    match tool {
+        Tool::Npm | Tool::Pnpm => list.args(["ls", "--global", "--depth=0", "--json"]),
+        Tool::Yarn => list.args(["global", "list", "--json"]),
+        Tool::Bun => list.args(["pm", "ls", "--global"]),
+    };
+    let output = match list.output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[{}] {}", tool.name(), e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    let packages: Vec<(String, String)> = match tool {
+        Tool::Npm => parse_npm(&lines),
+        Tool::Pnpm => parse_pnpm(&lines),
+        Tool::Yarn => parse_yarn(&lines),
+        Tool::Bun => parse_bun(&lines),
+    };
+
+    Ok(packages
+        .into_iter()
+        .map(|(name, version)| PackageResult::installed(tool.name(), &name, &version, "", "", ""))
+        .collect())
+}
+
+/// Splits `name@version`, keeping the `@` of scoped packages like `@vue/cli@5.0.8`.
+fn split_spec(spec: &str) -> Option<(String, String)> {
+    let (name, version) = spec.rsplit_once('@')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
+
+/// `{"dependencies": {"typescript": {"version": "5.6.2"}}}`
+fn parse_npm(lines: &[String]) -> Vec<(String, String)> {
+    match serde_json::from_str::<Tree>(&lines.join("\n")) {
+        Ok(tree) => tree
+            .dependencies
+            .into_iter()
+            .map(|(name, dependency)| (name, dependency.version))
+            .collect(),
+        Err(e) => {
+            warn!("[npm] {}", e);
+            vec![]
+        }
+    }
+}
+
+/// `[{"path": "...", "dependencies": {"typescript": {"version": "5.6.2"}}}]`
+fn parse_pnpm(lines: &[String]) -> Vec<(String, String)> {
+    match serde_json::from_str::<Vec<Tree>>(&lines.join("\n")) {
+        Ok(trees) => trees
+            .into_iter()
+            .flat_map(|tree| tree.dependencies)
+            .map(|(name, dependency)| (name, dependency.version))
+            .collect(),
+        Err(e) => {
+            warn!("[pnpm] {}", e);
+            vec![]
+        }
+    }
+}
+
+/// One json object per line, the packages are in the info messages:
+/// `{"type":"info","data":"\"typescript@5.6.2\" has binaries:"}`
+fn parse_yarn(lines: &[String]) -> Vec<(String, String)> {
+    #[derive(Deserialize)]
+    struct Message {
+        #[serde(rename = "type")]
+        kind: String,
+        data: serde_json::Value,
+    }
+
+    let mut packages = vec![];
+    for line in lines {
+        let Ok(message) = serde_json::from_str::<Message>(line) else {
+            warn!("[yarn] skipping line {line:?}");
+            continue;
+        };
+        if message.kind != "info" {
+            continue;
+        }
+        let Some(data) = message.data.as_str() else {
+            continue;
+        };
+        let Some(spec) = data
+            .strip_prefix('"')
+            .and_then(|data| data.split('"').next())
+        else {
+            continue;
+        };
+        if let Some(package) = split_spec(spec) {
+            packages.push(package);
+        }
+    }
+    packages
+}
+
+/// A tree without json support:
+/// ```text
+/// /home/user/.bun/install/global node_modules (2)
+/// ├── prettier@3.3.3
+/// └── typescript@5.6.2
+/// ```
+fn parse_bun(lines: &[String]) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .filter_map(|line| line.split_once("── "))
+        .filter_map(|(_, spec)| split_spec(spec.trim()))
+        .collect()
+}
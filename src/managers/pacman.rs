@@ -0,0 +1,346 @@
+use super::{command, command_line, sudo, which, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult, PackageStatus};
+use std::fs;
+use std::path::PathBuf;
+
+/// The local database of installed packages, one directory per package.
+const LOCAL_DB: &str = "/var/lib/pacman/local";
+
+/// AUR helpers in order of preference.
+const AUR_HELPERS: &[&str] = &["paru", "yay"];
+
+/// The `pacman` package manager, with the AUR through `paru` or `yay`.
+pub struct Pacman;
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &'static str {
+        "pacman"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_pacman(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_pacman()
+    }
+
+    fn search(&self, term: &str) -> Result<Vec<PackageResult>, String> {
+        search_pacman(term)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: true,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(LOCAL_DB))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Rpm
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        install_line(result)
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        // installing again upgrades the single package
+        install_line(result)
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("pacman", &["-R", &result.package])))
+    }
+}
+
+/// Repo packages are installed with pacman, AUR packages with the helper.
+fn install_line(result: &PackageResult) -> Option<Vec<String>> {
+    if result.repo == "aur" {
+        let helper = aur_helper()?;
+        Some(command_line(helper, &["-S", &result.package]))
+    } else {
+        Some(sudo(command_line("pacman", &["-S", &result.package])))
+    }
+}
+
+/// Returns the first AUR helper that is installed.
+fn aur_helper() -> Option<&'static str> {
+    AUR_HELPERS.iter().copied().find(|helper| which(helper))
+}
+
+/// A package entry of `pacman -Si` or `<helper> -Si --aur`.
+struct SyncInfo {
+    repo: String,
+    name: String,
+    version: String,
+    desc: String,
+}
+
+/// Parses the `Key : Value` blocks of `-Si`, a block starts with `Repository`.
+fn parse_sync_info(lines: &[String]) -> Vec<SyncInfo> {
+    let mut infos: Vec<SyncInfo> = vec![];
+    for line in lines {
+        // continuation lines of multi-line values start with whitespace
+        if line.starts_with(' ') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Repository" => infos.push(SyncInfo {
+                repo: value,
+                name: String::new(),
+                version: String::new(),
+                desc: String::new(),
+            }),
+            "Name" => {
+                if let Some(info) = infos.last_mut() {
+                    info.name = value;
+                }
+            }
+            "Version" => {
+                if let Some(info) = infos.last_mut() {
+                    info.version = value;
+                }
+            }
+            "Description" => {
+                if let Some(info) = infos.last_mut() {
+                    info.desc = value;
+                }
+            }
+            _ => (),
+        }
+    }
+    infos
+}
+
+/// Looks a package up in the sync databases, or the AUR if the repos don't have it.
+fn sync_info(package_name: &str) -> Result<Option<SyncInfo>, String> {
+    let output = match command("pacman").arg("-Si").arg(package_name).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[pacman] {}", e)),
+    };
+    // pacman exits with an error and prints nothing to stdout if the package is unknown
+    let lines = check_output(output).unwrap_or_default();
+    // several repos can have the package, pacman lists them in the order of
+    // pacman.conf and `pacman -S` always takes the first one, even if a later
+    // repo has a newer version, so that's the only one that matters here
+    let mut matches = parse_sync_info(&lines)
+        .into_iter()
+        .filter(|info| info.name == package_name);
+    if let Some(info) = matches.next() {
+        for shadowed in matches {
+            debug!(
+                "[pacman] {} {} in {} is shadowed by {}",
+                package_name, shadowed.version, shadowed.repo, info.repo
+            );
+        }
+        return Ok(Some(info));
+    }
+
+    let Some(helper) = aur_helper() else {
+        debug!("[pacman] no AUR helper installed");
+        return Ok(None);
+    };
+    let output = match command(helper)
+        .arg("-Si")
+        .arg("--aur")
+        .arg(package_name)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[{}] {}", helper, e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+    Ok(parse_sync_info(&lines)
+        .into_iter()
+        .find(|info| info.name == package_name)
+        .map(|info| SyncInfo {
+            repo: "aur".to_string(),
+            ..info
+        }))
+}
+
+/// Checks if a package is available or installed using `pacman` and an AUR helper.
+pub fn check_pacman(package_name: &str) -> Result<PackageResult, String> {
+    // ------------------------------------------------------
+    // 1. check the sync databases and the AUR
+    // ------------------------------------------------------
+    let info = sync_info(package_name)?;
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = list_pacman()?
+        .into_iter()
+        .find(|installed| installed.package == package_name);
+
+    match (info, installed) {
+        (Some(info), Some(installed)) => {
+            let local_version = installed.installed_version.unwrap_or_default();
+            let result = PackageResult::installed(
+                "pacman",
+                &info.name,
+                &local_version,
+                &info.version,
+                &info.desc,
+                &info.repo,
+            );
+            // being outdated is more important to show than being a dependency
+            if installed.status == PackageStatus::InstalledAutomatic
+                && result.status == PackageStatus::Installed
+            {
+                return Ok(result.with_status(PackageStatus::InstalledAutomatic));
+            }
+            Ok(result)
+        }
+        // built locally or dropped from the repos
+        (None, Some(installed)) => Ok(PackageResult {
+            repo: "local".to_string(),
+            ..installed
+        }),
+        (Some(info), None) => Ok(PackageResult::available(
+            "pacman",
+            &info.name,
+            &info.version,
+            &info.desc,
+            &info.repo,
+        )),
+        (None, None) => Ok(PackageResult::none("pacman", package_name)),
+    }
+}
+
+/// Searches the sync databases with `pacman -Ss`, or the AUR helper which
+/// also searches the AUR.
+pub fn search_pacman(term: &str) -> Result<Vec<PackageResult>, String> {
+    let tool = aur_helper().unwrap_or("pacman");
+    let output = match command(tool).arg("-Ss").arg(term).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[{}] {}", tool, e)),
+    };
+    // nothing found means empty output and an error
+    let lines = check_output(output).unwrap_or_default();
+    let installed = list_pacman()?;
+
+    // extra/ripgrep 14.1.0-1 (1.2 MiB 4.5 MiB) [installed]
+    //     A search tool that combines the usability of ag with the raw speed of grep
+    // aur/ripgrep-git 14.1.0.r12-1 (+5 0.00) (Installed: 14.0.0.r3-1)
+    let mut found: Vec<PackageResult> = vec![];
+    for line in &lines {
+        if line.starts_with(' ') {
+            if let Some(result) = found.last_mut()
+                && result.desc.is_empty()
+            {
+                result.desc = line.trim().to_string();
+            }
+            continue;
+        }
+        let mut chunks = line.split_whitespace();
+        let (Some((repo, name)), Some(version)) = (
+            chunks.next().and_then(|chunk| chunk.split_once('/')),
+            chunks.next(),
+        ) else {
+            warn!("[pacman] skipping line {line:?}");
+            continue;
+        };
+        let result = match installed.iter().find(|installed| installed.package == name) {
+            Some(installed) => {
+                let result = PackageResult::installed(
+                    "pacman",
+                    name,
+                    installed.installed_version.as_deref().unwrap_or_default(),
+                    version,
+                    "",
+                    repo,
+                );
+                if installed.status == PackageStatus::InstalledAutomatic
+                    && result.status == PackageStatus::Installed
+                {
+                    result.with_status(PackageStatus::InstalledAutomatic)
+                } else {
+                    result
+                }
+            }
+            None => PackageResult::available("pacman", name, version, "", repo),
+        };
+        found.push(result);
+    }
+
+    Ok(found)
+}
+
+/// Parses a `desc` file of the local database into its `%KEY%` sections.
+fn parse_desc(content: &str) -> Vec<(&str, Vec<&str>)> {
+    let mut sections: Vec<(&str, Vec<&str>)> = vec![];
+    for line in content.lines() {
+        if line.len() > 2 && line.starts_with('%') && line.ends_with('%') {
+            sections.push((&line[1..line.len() - 1], vec![]));
+        } else if !line.is_empty()
+            && let Some((_, values)) = sections.last_mut()
+        {
+            values.push(line);
+        }
+    }
+    sections
+}
+
+/// Lists all installed packages by reading the local pacman database.
+pub fn list_pacman() -> Result<Vec<PackageResult>, String> {
+    let entries = match fs::read_dir(LOCAL_DB) {
+        Ok(entries) => entries,
+        // pacman can exist without a system to manage, e.g. on other distros
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("[pacman] no local database at {}", LOCAL_DB);
+            return Ok(vec![]);
+        }
+        Err(e) => return Err(format!("[pacman] could not read {}: {}", LOCAL_DB, e)),
+    };
+
+    let mut installed = vec![];
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path().join("desc");
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            // ALPM_DB_VERSION is a plain file next to the package directories
+            Err(_) => continue,
+        };
+
+        let sections = parse_desc(&content);
+        let value = |key: &str| {
+            sections
+                .iter()
+                .find(|(name, _)| *name == key)
+                .and_then(|(_, values)| values.first().copied())
+                .unwrap_or_default()
+        };
+        let name = value("NAME");
+        if name.is_empty() {
+            warn!("[pacman] no name in {}", path.display());
+            continue;
+        }
+
+        let result =
+            PackageResult::installed("pacman", name, value("VERSION"), "", value("DESC"), "");
+        // reason 1 means it was installed as a dependency
+        if value("REASON") == "1" {
+            installed.push(result.with_status(PackageStatus::InstalledAutomatic));
+        } else {
+            installed.push(result);
+        }
+    }
+
+    installed.sort_by(|a, b| a.package.cmp(&b.package));
+    Ok(installed)
+}
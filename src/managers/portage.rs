@@ -0,0 +1,372 @@
+use super::{command, command_line, sudo, which, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult, PackageStatus};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The installed package database, one `category/name-version` directory per package.
+const VDB: &str = "/var/db/pkg";
+/// The packages that were emerged on purpose.
+const WORLD: &str = "/var/lib/portage/world";
+
+/// Gentoo's portage, installed packages come from its database and
+/// candidates from `portageq`.
+pub struct Portage;
+
+impl PackageManager for Portage {
+    fn name(&self) -> &'static str {
+        "portage"
+    }
+
+    fn detect(&self) -> bool {
+        which("emerge")
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_portage(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_portage()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(VDB))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Gentoo
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("emerge", &["--ask", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line(
+            "emerge",
+            &["--ask", "--update", &result.package],
+        )))
+    }
+
+    // deselects it and removes it if nothing else needs it
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line(
+            "emerge",
+            &["--ask", "--depclean", &result.package],
+        )))
+    }
+}
+
+/// Splits `name-version` like `curl-8.9.1-r1`, names can contain dashes and
+/// digits, so the version is the part after the first dash followed by a
+/// digit that contains no other dash (except the revision).
+fn split_version(package: &str) -> Option<(&str, &str)> {
+    package.match_indices('-').find_map(|(index, _)| {
+        let version = &package[index + 1..];
+        let upstream = match version.rsplit_once("-r") {
+            Some((upstream, revision)) if revision.chars().all(|c| c.is_ascii_digit()) => upstream,
+            _ => version,
+        };
+        if version.starts_with(|c: char| c.is_ascii_digit()) && !upstream.contains('-') {
+            Some((&package[..index], version))
+        } else {
+            None
+        }
+    })
+}
+
+/// Reads a metadata file of an installed package, empty if it's missing.
+fn read_meta(dir: &Path, key: &str) -> String {
+    fs::read_to_string(dir.join(key))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Returns the `category/name` atoms in the world file.
+fn world() -> Vec<String> {
+    fs::read_to_string(WORLD)
+        .unwrap_or_default()
+        .lines()
+        .map(|atom| atom.split(':').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Returns the categories of all ebuild repositories that have a package with this name.
+fn categories(package_name: &str) -> Result<Vec<String>, String> {
+    let output = match command("portageq").arg("get_repos").arg("/").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[portage] {}", e)),
+    };
+    let repos: Vec<String> = check_output(output)
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|line| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if repos.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let output = match command("portageq")
+        .arg("get_repo_path")
+        .arg("/")
+        .args(&repos)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[portage] {}", e)),
+    };
+    let mut categories = vec![];
+    for path in check_output(output).unwrap_or_default() {
+        let Ok(entries) = fs::read_dir(&path) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let category = entry.file_name().to_string_lossy().to_string();
+            if (category.contains('-') || category == "virtual")
+                && entry.path().join(package_name).is_dir()
+                && !categories.contains(&category)
+            {
+                categories.push(category);
+            }
+        }
+    }
+    // a virtual only points to the real package
+    categories.sort_by_key(|category| category == "virtual");
+    Ok(categories)
+}
+
+/// Returns the best visible version, its repository and description for an atom,
+/// `None` if everything is masked.
+fn best_visible(atom: &str) -> Result<Option<(String, String, String)>, String> {
+    let output = match command("portageq")
+        .arg("best_visible")
+        .arg("/")
+        .arg("ebuild")
+        .arg(atom)
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[portage] {}", e)),
+    };
+    // net-misc/curl-8.9.1-r1
+    let Some(cpv) = check_output(output).unwrap_or_default().into_iter().next() else {
+        return Ok(None);
+    };
+    let Some((_, version)) = split_version(&cpv) else {
+        warn!("[portage] unexpected version {cpv:?}");
+        return Ok(None);
+    };
+    let version = version.to_string();
+
+    let output = match command("portageq")
+        .arg("metadata")
+        .arg("/")
+        .arg("ebuild")
+        .arg(&cpv)
+        .arg("repository")
+        .arg("DESCRIPTION")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[portage] {}", e)),
+    };
+    let mut lines = check_output(output).unwrap_or_default().into_iter();
+    let repo = lines.next().unwrap_or_default();
+    let desc = lines.next().unwrap_or_default();
+    Ok(Some((version, repo, desc)))
+}
+
+/// Returns the installed versions of `category/name` straight from the package
+/// database, several if it's installed in more than one slot.
+fn installed_in(category: &str, name: &str, world: &[String]) -> Vec<PackageResult> {
+    let Ok(packages) = fs::read_dir(Path::new(VDB).join(category)) else {
+        return vec![];
+    };
+    packages
+        .flatten()
+        .filter(|package| {
+            package
+                .file_name()
+                .to_string_lossy()
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with('-'))
+        })
+        .filter_map(|package| read_package(category, &package.path(), world))
+        .filter(|installed| installed.package.split_once('/').map(|(_, name)| name) == Some(name))
+        .collect()
+}
+
+/// Leaves out the virtual category if there are others, a virtual only points to the real package.
+fn without_virtual(mut categories: Vec<&String>) -> Vec<&String> {
+    if categories.iter().any(|category| *category != "virtual") {
+        categories.retain(|category| *category != "virtual");
+    }
+    categories
+}
+
+/// Checks if a package is available or installed using portage.
+/// The package can be given with or without its category, without one it has
+/// to be unique like for `emerge`.
+pub fn check_portage(package_name: &str) -> Result<PackageResult, String> {
+    // ---------------------------------------------
+    // 1. find the category, unless it's in the atom
+    // ---------------------------------------------
+    let (categories, name) = match package_name.split_once('/') {
+        Some((category, name)) => (vec![category.to_string()], name),
+        None => (categories(package_name)?, package_name),
+    };
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let world = world();
+    let installed_in: Vec<(String, Vec<PackageResult>)> = categories
+        .iter()
+        .map(|category| (category.clone(), installed_in(category, name, &world)))
+        .collect();
+    // like emerge, an installed package wins over the others with that name
+    let installed_categories = without_virtual(
+        installed_in
+            .iter()
+            .filter(|(_, installed)| !installed.is_empty())
+            .map(|(category, _)| category)
+            .collect(),
+    );
+    let candidates = if installed_categories.is_empty() {
+        without_virtual(categories.iter().collect())
+    } else {
+        installed_categories
+    };
+    let category = match candidates.as_slice() {
+        [] => None,
+        [category] => Some(category.to_string()),
+        _ => {
+            return Err(format!(
+                "[portage] {} is ambiguous, use one of {}",
+                package_name,
+                candidates
+                    .iter()
+                    .map(|category| format!("{}/{}", category, name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    };
+    let installed = installed_in
+        .into_iter()
+        .find(|(installed_category, _)| Some(installed_category) == category.as_ref())
+        .and_then(|(_, installed)| {
+            installed.into_iter().max_by(|a, b| {
+                Scheme::Gentoo.compare(
+                    a.installed_version.as_deref().unwrap_or_default(),
+                    b.installed_version.as_deref().unwrap_or_default(),
+                )
+            })
+        });
+
+    // -------------------------------------------------------
+    // 3. find the package in the repos and its newest version
+    // -------------------------------------------------------
+    let atom = category.map(|category| format!("{}/{}", category, name));
+    let found = match &atom {
+        Some(atom) => best_visible(atom)?,
+        None => None,
+    };
+
+    match (found, installed) {
+        (Some((version, repo, desc)), Some(installed)) => {
+            let result = PackageResult::installed(
+                "portage",
+                &installed.package,
+                installed.installed_version.as_deref().unwrap_or_default(),
+                &version,
+                &desc,
+                &repo,
+            );
+            if installed.status == PackageStatus::InstalledAutomatic
+                && result.status == PackageStatus::Installed
+            {
+                Ok(result.with_status(PackageStatus::InstalledAutomatic))
+            } else {
+                Ok(result)
+            }
+        }
+        (None, Some(installed)) => Ok(installed),
+        (Some((version, repo, desc)), None) => Ok(PackageResult::available(
+            "portage",
+            atom.as_deref().unwrap_or(package_name),
+            &version,
+            &desc,
+            &repo,
+        )),
+        (None, None) => Ok(PackageResult::none("portage", package_name)),
+    }
+}
+
+/// Reads an installed package from its directory in the package database,
+/// it's automatic if it's not in the world file.
+fn read_package(category: &str, dir: &Path, world: &[String]) -> Option<PackageResult> {
+    let dir_name = dir.file_name()?.to_string_lossy().to_string();
+    let Some((name, version)) = split_version(&dir_name) else {
+        warn!("[portage] skipping {category}/{dir_name}");
+        return None;
+    };
+    let atom = format!("{}/{}", category, name);
+    let result = PackageResult::installed(
+        "portage",
+        &atom,
+        version,
+        "",
+        &read_meta(dir, "DESCRIPTION"),
+        &read_meta(dir, "repository"),
+    );
+    if world.contains(&atom) {
+        Some(result)
+    } else {
+        Some(result.with_status(PackageStatus::InstalledAutomatic))
+    }
+}
+
+/// Lists all installed packages by reading the package database,
+/// packages that aren't in the world file are automatic.
+pub fn list_portage() -> Result<Vec<PackageResult>, String> {
+    let categories = match fs::read_dir(VDB) {
+        Ok(categories) => categories,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("[portage] {}: {}", VDB, e)),
+    };
+    let world = world();
+
+    let mut installed = vec![];
+    for category in categories.flatten() {
+        let Ok(packages) = fs::read_dir(category.path()) else {
+            continue;
+        };
+        let category = category.file_name().to_string_lossy().to_string();
+        installed.extend(
+            packages
+                .flatten()
+                .filter_map(|package| read_package(&category, &package.path(), &world)),
+        );
+    }
+    installed.sort_by(|a, b| a.package.cmp(&b.package));
+
+    Ok(installed)
+}
@@ -0,0 +1,351 @@
+use super::{binary, command, command_line, which, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, config, PackageResult};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The python package managers, the latest versions all come from `pip index versions`.
+#[derive(Debug, Clone, Copy)]
+enum Tool {
+    /// the packages in the user and system site-packages of its interpreter
+    Pip,
+    /// every application in its own venv
+    Pipx,
+    /// `uv tool`, like pipx every tool gets its own venv
+    Uv,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Pip => "pip",
+            Tool::Pipx => "pipx",
+            Tool::Uv => "uv",
+        }
+    }
+
+    fn list_installed(self) -> Result<Vec<PackageResult>, String> {
+        match self {
+            Tool::Pip => list_pip(),
+            Tool::Pipx => list_pipx(),
+            Tool::Uv => list_uv(),
+        }
+    }
+}
+
+/// A python package manager, one for each [`Tool`].
+pub struct Python(Tool);
+
+/// `pip`, the packages in the user and system site-packages of its interpreter.
+pub const PIP: Python = Python(Tool::Pip);
+/// `pipx`, every application in its own venv.
+pub const PIPX: Python = Python(Tool::Pipx);
+/// `uv tool`, like pipx every tool gets its own venv.
+pub const UV: Python = Python(Tool::Uv);
+
+impl PackageManager for Python {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_python(self.name(), package_name, self.0.list_installed()?)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        self.0.list_installed()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Pep440
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(match self.0 {
+            Tool::Pip | Tool::Pipx => with_index(self.name(), &["install", &result.package]),
+            Tool::Uv => with_index("uv", &["tool", "install", &result.package]),
+        })
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(match self.0 {
+            Tool::Pip => with_index("pip", &["install", "--upgrade", &result.package]),
+            Tool::Pipx => command_line("pipx", &["upgrade", &result.package]),
+            Tool::Uv => with_index("uv", &["tool", "upgrade", &result.package]),
+        })
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(match self.0 {
+            Tool::Pip | Tool::Pipx => command_line(self.name(), &["uninstall", &result.package]),
+            Tool::Uv => command_line("uv", &["tool", "uninstall", &result.package]),
+        })
+    }
+}
+
+/// The index to use, the configured `source` or `$PIP_INDEX_URL`, `None` for PyPI.
+fn index_url(manager: &str) -> Option<String> {
+    config::get()
+        .manager(manager)
+        .source
+        .or_else(|| std::env::var("PIP_INDEX_URL").ok())
+        .filter(|url| !url.is_empty())
+}
+
+/// Builds a command line that downloads from the configured index.
+fn with_index(manager: &str, args: &[&str]) -> Vec<String> {
+    let index = index_url(manager);
+    let mut args = args.to_vec();
+    if let Some(index) = &index {
+        args.extend(["--index-url", index]);
+    }
+    command_line(manager, &args)
+}
+
+/// Normalizes a project name like PyPI does, `Foo_Bar` and `foo-bar` are the same.
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// Looks up the latest version on the index with `pip index versions`,
+/// `None` if it's not there.
+fn latest_version(manager: &str, package_name: &str) -> Result<Option<String>, String> {
+    // pipx and uv don't have a way to look versions up themselves
+    if !which(&binary("pip")) {
+        return Err(format!(
+            "[{}] pip is needed to look up the latest versions",
+            manager
+        ));
+    }
+    let mut pip = command("pip");
+    pip.arg("index").arg("versions").arg(package_name);
+    if let Some(index) = index_url(manager) {
+        pip.arg("--index-url").arg(index);
+    }
+    let output = match pip.output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[{}] pip index: {}", manager, e)),
+    };
+    // "ERROR: No matching distribution found for ..." and a failing status,
+    // anything else like being offline is an error
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("No matching distribution") {
+            debug!("[{}] {}", manager, stderr.trim());
+            return Ok(None);
+        }
+        // the warnings about retries come first, the reason last
+        let message = stderr
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .unwrap_or("pip index failed");
+        return Err(format!("[{}] {}", manager, message.trim()));
+    }
+    let lines = check_output(output).unwrap_or_default();
+
+    // requests (2.32.3)
+    // Available versions: 2.32.3, 2.32.2, ...
+    let Some(first) = lines.first() else {
+        return Ok(None);
+    };
+    let version = first
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(version, _)| version.to_string());
+    if version.is_none() {
+        warn!("[{}] unexpected pip index output {first:?}", manager);
+    }
+    Ok(version)
+}
+
+/// Checks if a package is available or installed, given the packages the manager installed.
+fn check_python(
+    manager: &str,
+    package_name: &str,
+    installed: Vec<PackageResult>,
+) -> Result<PackageResult, String> {
+    // ----------------------------------------
+    // 1. check the index if the package exists
+    // ----------------------------------------
+    let latest = latest_version(manager, package_name)?;
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = installed
+        .into_iter()
+        .find(|installed| normalize(&installed.package) == normalize(package_name));
+
+    match (latest, installed) {
+        (Some(latest), Some(installed)) => Ok(PackageResult::installed(
+            manager,
+            &installed.package,
+            installed.installed_version.as_deref().unwrap_or_default(),
+            &latest,
+            &installed.desc,
+            &installed.repo,
+        )
+        .with_location(installed.location.as_deref().unwrap_or_default())),
+        // installed from a local path, a git repo or another index
+        (None, Some(installed)) => Ok(installed),
+        (Some(latest), None) => Ok(PackageResult::available(
+            manager,
+            package_name,
+            &latest,
+            "",
+            "",
+        )),
+        (None, None) => Ok(PackageResult::none(manager, package_name)),
+    }
+}
+
+/// An entry of `pip list --format=json --verbose`.
+#[derive(Deserialize)]
+struct PipPackage {
+    name: String,
+    version: String,
+    /// the site-packages directory, tells apart user, system and venv installs
+    #[serde(default)]
+    location: String,
+}
+
+/// Lists all packages of the interpreter `pip` belongs to.
+fn list_pip() -> Result<Vec<PackageResult>, String> {
+    let output = match command("pip")
+        .arg("list")
+        .arg("--format=json")
+        .arg("--verbose")
+        .arg("--disable-pip-version-check")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[pip] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+    if lines.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let packages: Vec<PipPackage> = match serde_json::from_str(&lines.join("\n")) {
+        Ok(packages) => packages,
+        Err(e) => return Err(format!("[pip] {}", e)),
+    };
+    Ok(packages
+        .into_iter()
+        .map(|package| {
+            PackageResult::installed("pip", &package.name, &package.version, "", "", "")
+                .with_location(&package.location)
+        })
+        .collect())
+}
+
+/// The output of `pipx list --json`.
+#[derive(Deserialize)]
+struct PipxList {
+    #[serde(default)]
+    venvs: BTreeMap<String, PipxVenv>,
+}
+
+#[derive(Deserialize)]
+struct PipxVenv {
+    metadata: PipxMetadata,
+}
+
+#[derive(Deserialize)]
+struct PipxMetadata {
+    main_package: PipxPackage,
+    #[serde(default)]
+    python_version: String,
+}
+
+#[derive(Deserialize)]
+struct PipxPackage {
+    package: String,
+    #[serde(default)]
+    package_version: String,
+}
+
+/// Lists all applications installed with `pipx`.
+fn list_pipx() -> Result<Vec<PackageResult>, String> {
+    let output = match command("pipx").arg("list").arg("--json").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[pipx] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+    if lines.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let list: PipxList = match serde_json::from_str(&lines.join("\n")) {
+        Ok(list) => list,
+        Err(e) => return Err(format!("[pipx] {}", e)),
+    };
+    // the venv is named after the package, the interpreter tells them apart
+    Ok(list
+        .venvs
+        .into_values()
+        .map(|venv| {
+            let package = venv.metadata.main_package;
+            PackageResult::installed(
+                "pipx",
+                &package.package,
+                &package.package_version,
+                "",
+                "",
+                "",
+            )
+            .with_location(&venv.metadata.python_version)
+        })
+        .collect())
+}
+
+/// Lists all tools installed with `uv tool`.
+fn list_uv() -> Result<Vec<PackageResult>, String> {
+    let output = match command("uv")
+        .arg("tool")
+        .arg("list")
+        .arg("--show-paths")
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[uv] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    // ruff v0.6.9 (/home/user/.local/share/uv/tools/ruff)
+    // - ruff (/home/user/.local/bin/ruff)
+    let mut installed = vec![];
+    for line in &lines {
+        if line.starts_with('-') {
+            continue;
+        }
+        let (tool, location) = match line.split_once(" (") {
+            Some((tool, location)) => (tool, location.trim_end_matches(')')),
+            None => (line.as_str(), ""),
+        };
+        // "No tools installed" has no version
+        let Some((name, version)) = tool
+            .split_once(" v")
+            .filter(|(_, version)| version.starts_with(|c: char| c.is_ascii_digit()))
+        else {
+            debug!("[uv] skipping line {line:?}");
+            continue;
+        };
+        installed.push(
+            PackageResult::installed("uv", name, version, "", "", "").with_location(location),
+        );
+    }
+
+    Ok(installed)
+}
@@ -1,7 +1,56 @@
+use super::{command, command_line, sudo, Capabilities, PackageManager};
 use crate::{check_output, reduce_whitespace, PackageResult};
-use std::process::Command;
+use std::path::PathBuf;
 use strp::*;
 
+/// The `snap` package manager.
+pub struct Snap;
+
+impl PackageManager for Snap {
+    fn name(&self) -> &'static str {
+        "snap"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_snap(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_snap()
+    }
+
+    fn search(&self, term: &str) -> Result<Vec<PackageResult>, String> {
+        search_snap(term)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: true,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/var/lib/snapd/state.json"))
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("snap", &["install", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("snap", &["refresh", &result.package])))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("snap", &["remove", &result.package])))
+    }
+}
+
 /// Checks if a package is available or installed using the `snap` package manager.
 pub fn check_snap(package_name: &str) -> Result<PackageResult, String> {
     // found: {name} {version} {_} {_} {summary}
@@ -11,7 +60,7 @@ pub fn check_snap(package_name: &str) -> Result<PackageResult, String> {
     // -----------------------------------
     // 1. check registry if package exists
     // -----------------------------------
-    let output = match Command::new("snap").arg("find").arg(package_name).output() {
+    let output = match command("snap").arg("find").arg(package_name).output() {
         Ok(output) => output,
         Err(e) => return Err(format!("[snap] {}", e)),
     };
@@ -34,7 +83,7 @@ pub fn check_snap(package_name: &str) -> Result<PackageResult, String> {
     if lines.iter().all(|line| !line.contains(package_name)) {
         return Result::Ok(PackageResult::none("snap", package_name));
     }
-    fn check_exact_name(line: &String, package_name: &str) -> bool {
+    fn check_exact_name(line: &str, package_name: &str) -> bool {
         let mut iter = line.split_whitespace();
         let name: &str = iter.next().unwrap_or_default();
         name == package_name
@@ -78,32 +127,89 @@ pub fn check_snap(package_name: &str) -> Result<PackageResult, String> {
     // --------------------------------
     // 3. check if package is installed
     // --------------------------------
+    for installed in list_snap()? {
+        if package_name == installed.package {
+            let local_version = installed.installed_version.unwrap_or_default();
+            return Result::Ok(PackageResult::installed(
+                "snap",
+                &installed.package,
+                &local_version,
+                &version,
+                &desc,
+                "",
+            ));
+        }
+    }
+
+    Result::Ok(PackageResult::available(
+        "snap",
+        package_name,
+        &version,
+        &desc,
+        "",
+    ))
+}
+
+/// Searches the snap store for snaps matching the term using `snap find`.
+pub fn search_snap(term: &str) -> Result<Vec<PackageResult>, String> {
+    let output = match command("snap").arg("find").arg(term).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[snap] {}", e)),
+    };
+    // "No matching snaps" goes to stderr
+    let lines = check_output(output).unwrap_or_default();
+    let installed = list_snap()?;
+
+    let mut found = vec![];
+    // Name     Version  Publisher   Notes    Summary
+    // ripgrep  12.1.0   mmstick26   classic  Search directories for a regex pattern
+    for line in lines.iter().skip(1) {
+        let mut chunks = line.split_whitespace();
+        let (Some(name), Some(version), Some(_), Some(_)) =
+            (chunks.next(), chunks.next(), chunks.next(), chunks.next())
+        else {
+            warn!("[snap] skipping line {line:?}");
+            continue;
+        };
+        let desc = chunks.collect::<Vec<_>>().join(" ");
+        let result = match installed.iter().find(|installed| installed.package == name) {
+            Some(installed) => PackageResult::installed(
+                "snap",
+                name,
+                installed.installed_version.as_deref().unwrap_or_default(),
+                version,
+                &desc,
+                "",
+            ),
+            None => PackageResult::available("snap", name, version, &desc, ""),
+        };
+        found.push(result);
+    }
+
+    Ok(found)
+}
+
+/// Lists all packages installed with `snap`.
+pub fn list_snap() -> Result<Vec<PackageResult>, String> {
     // run command
-    let output = match Command::new("snap").arg("list").output() {
+    let output = match command("snap").arg("list").output() {
         Ok(output) => output,
         Err(e) => return Err(e.to_string()),
     };
 
     // check for empty output
-    let lines = match check_output(output) {
-        Ok(lines) => lines,
-        Err(e) => return Err(e),
-    };
+    let lines = check_output(output).unwrap_or_default();
 
-    // remove the first line (header)
-    match lines.first() {
-        Some(_) => (),
-        None => return Err("snap show output is empty".to_owned()),
-    };
-
-    // filter the lines
+    // skip the first line (header) and filter the rest
     let filtered_lines: Vec<String> = lines
         .iter()
-        .filter(|line| !line.starts_with(' ') && !line.is_empty() && line.contains(package_name))
+        .skip(1)
+        .filter(|line| !line.starts_with(' ') && !line.is_empty())
         .map(|line| line.to_string())
         .collect();
 
     // loop over, reduce, scan and extract version
+    let mut installed = vec![];
     for line in &filtered_lines {
         let reduced_line = reduce_whitespace(line.to_string());
         let scanned: Result<(String, String, String), _> = try_scan!(reduced_line => "{} {} {}");
@@ -112,29 +218,15 @@ pub fn check_snap(package_name: &str) -> Result<PackageResult, String> {
             Err(e) => return Err(format!("parsing error: {e:?}")),
         };
 
-        if package_name == name {
-            let version_info: String = if local_version != version {
-                format!("{} -> {}", local_version, version)
-            } else {
-                local_version
-            };
-            return Result::Ok(PackageResult::some(
-                "snap",
-                &name,
-                "installed",
-                &version_info,
-                &desc,
-                "",
-            ));
-        }
+        installed.push(PackageResult::installed(
+            "snap",
+            &name,
+            &local_version,
+            "",
+            "",
+            "",
+        ));
     }
 
-    Result::Ok(PackageResult::some(
-        "snap",
-        package_name,
-        "available",
-        &version,
-        &desc,
-        "",
-    ))
+    Ok(installed)
 }
@@ -0,0 +1,200 @@
+use super::{command, command_line, sudo, which, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult, PackageStatus};
+use std::path::PathBuf;
+
+/// The Void Linux package manager, `xbps-query` and friends.
+pub struct Xbps;
+
+impl PackageManager for Xbps {
+    fn name(&self) -> &'static str {
+        "xbps"
+    }
+
+    fn detect(&self) -> bool {
+        which("xbps-query")
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_xbps(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_xbps()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/var/db/xbps"))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Rpm
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("xbps-install", &["-S", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line(
+            "xbps-install",
+            &["-Su", &result.package],
+        )))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("xbps-remove", &["-R", &result.package])))
+    }
+}
+
+/// The properties of a package from `xbps-query --show`.
+#[derive(Default)]
+struct Show {
+    version: String,
+    repository: String,
+    short_desc: String,
+    automatic: bool,
+}
+
+/// Splits a `pkgver` like `curl-8.9.1_1` into name and version.
+fn split_pkgver(pkgver: &str) -> Option<(&str, &str)> {
+    pkgver.rsplit_once('-')
+}
+
+/// Shows a package from the repos, or from the installed packages if `remote` is false,
+/// `None` if it's not there.
+fn show(package_name: &str, remote: bool) -> Result<Option<Show>, String> {
+    let mut query = command("xbps-query");
+    if remote {
+        query.arg("--repository");
+    }
+    let output = match query.arg("--show").arg(package_name).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[xbps] {}", e)),
+    };
+    // unknown packages exit with 2 and print nothing
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let lines = check_output(output).unwrap_or_default();
+
+    // pkgver: curl-8.9.1_1
+    // repository: https://repo-default.voidlinux.org/current
+    // short_desc: Client that groks URLs
+    let mut show = Show::default();
+    for line in &lines {
+        let Some((key, value)) = line.split_once(": ") else {
+            continue;
+        };
+        match key {
+            "pkgver" => {
+                show.version = split_pkgver(value)
+                    .map(|(_, version)| version.to_string())
+                    .unwrap_or_default();
+            }
+            "repository" => show.repository = value.to_string(),
+            "short_desc" => show.short_desc = value.to_string(),
+            "automatic-install" => show.automatic = value == "yes",
+            _ => {}
+        }
+    }
+    Ok(Some(show))
+}
+
+/// Checks if a package is available or installed using `xbps-query`.
+pub fn check_xbps(package_name: &str) -> Result<PackageResult, String> {
+    // --------------------------------
+    // 1. check repos if package exists
+    // --------------------------------
+    let found = show(package_name, true)?;
+
+    // --------------------------------
+    // 2. check if package is installed
+    // --------------------------------
+    let installed = show(package_name, false)?;
+
+    match (found, installed) {
+        (Some(found), Some(installed)) => {
+            let result = PackageResult::installed(
+                "xbps",
+                package_name,
+                &installed.version,
+                &found.version,
+                &found.short_desc,
+                &found.repository,
+            );
+            if installed.automatic && result.status == PackageStatus::Installed {
+                Ok(result.with_status(PackageStatus::InstalledAutomatic))
+            } else {
+                Ok(result)
+            }
+        }
+        // installed from a local repo or one that's gone
+        (None, Some(installed)) => Ok(PackageResult::installed(
+            "xbps",
+            package_name,
+            &installed.version,
+            "",
+            &installed.short_desc,
+            &installed.repository,
+        )),
+        (Some(found), None) => Ok(PackageResult::available(
+            "xbps",
+            package_name,
+            &found.version,
+            &found.short_desc,
+            &found.repository,
+        )),
+        (None, None) => Ok(PackageResult::none("xbps", package_name)),
+    }
+}
+
+/// Lists all packages installed with xbps, the ones not in `xbps-query -m` are automatic.
+pub fn list_xbps() -> Result<Vec<PackageResult>, String> {
+    let output = match command("xbps-query").arg("--list-manual-pkgs").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[xbps] {}", e)),
+    };
+    // curl-8.9.1_1
+    let manual: Vec<String> = check_output(output)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|pkgver| split_pkgver(pkgver).map(|(name, _)| name.to_string()))
+        .collect();
+
+    let output = match command("xbps-query").arg("--list-pkgs").output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[xbps] {}", e)),
+    };
+    let lines = check_output(output).unwrap_or_default();
+
+    let mut installed = vec![];
+    for line in &lines {
+        // ii curl-8.9.1_1                      Client that groks URLs
+        let mut chunks = line.split_whitespace();
+        chunks.next();
+        let Some((name, version)) = chunks.next().and_then(split_pkgver) else {
+            warn!("[xbps] skipping line {line:?}");
+            continue;
+        };
+        let desc = chunks.collect::<Vec<_>>().join(" ");
+        let mut result = PackageResult::installed("xbps", name, version, "", &desc, "");
+        if !manual.iter().any(|manual| manual == name) {
+            result = result.with_status(PackageStatus::InstalledAutomatic);
+        }
+        installed.push(result);
+    }
+
+    Ok(installed)
+}
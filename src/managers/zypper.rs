@@ -0,0 +1,191 @@
+use super::{command, command_line, sudo, Capabilities, PackageManager};
+use crate::version::Scheme;
+use crate::{check_output, PackageResult};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// The `zypper` package manager, read through its XML output.
+pub struct Zypper;
+
+impl PackageManager for Zypper {
+    fn name(&self) -> &'static str {
+        "zypper"
+    }
+
+    fn query(&self, package_name: &str) -> Result<PackageResult, String> {
+        check_zypper(package_name)
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, String> {
+        list_zypper()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            query: true,
+            list_installed: true,
+            install: true,
+            update: true,
+            remove: true,
+            search: false,
+        }
+    }
+
+    fn database(&self) -> Option<PathBuf> {
+        Some(PathBuf::from("/var/lib/rpm"))
+    }
+
+    fn version_scheme(&self) -> Scheme {
+        Scheme::Rpm
+    }
+
+    fn install_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("zypper", &["install", &result.package])))
+    }
+
+    fn update_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("zypper", &["update", &result.package])))
+    }
+
+    fn remove_command(&self, result: &PackageResult) -> Option<Vec<String>> {
+        Some(sudo(command_line("zypper", &["remove", &result.package])))
+    }
+}
+
+/// One `<solvable>` element of `zypper --xmlout search --details`.
+struct Solvable {
+    name: String,
+    /// `installed`, `not-installed` or `other-version`
+    status: String,
+    edition: String,
+    repository: String,
+    summary: String,
+}
+
+/// Runs `zypper --xmlout search` with the given arguments and returns the packages.
+/// The status and names are attributes, so this works regardless of the locale.
+fn search(args: &[&str]) -> Result<Vec<Solvable>, String> {
+    let output = match zypper().arg("search").args(args).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("[zypper] {}", e)),
+    };
+    // no matches exit with 104, but the xml is printed anyway
+    let lines = check_output(output).unwrap_or_default();
+    if lines.is_empty() {
+        return Ok(vec![]);
+    }
+    let xml = lines.join("\n");
+
+    let document = match roxmltree::Document::parse(&xml) {
+        Ok(document) => document,
+        Err(e) => return Err(format!("[zypper] {}", e)),
+    };
+
+    // <solvable status="installed" name="git" summary="..." kind="package"
+    //           edition="2.46.0-1.1" arch="x86_64" repository="repo-oss"/>
+    let mut solvables = vec![];
+    for node in document
+        .descendants()
+        .filter(|n| n.has_tag_name("solvable"))
+    {
+        if node.attribute("kind").is_some_and(|kind| kind != "package") {
+            continue;
+        }
+        let Some(name) = node.attribute("name") else {
+            warn!("[zypper] skipping solvable without a name");
+            continue;
+        };
+        solvables.push(Solvable {
+            name: name.to_string(),
+            status: node.attribute("status").unwrap_or_default().to_string(),
+            edition: node.attribute("edition").unwrap_or_default().to_string(),
+            repository: node.attribute("repository").unwrap_or_default().to_string(),
+            summary: node.attribute("summary").unwrap_or_default().to_string(),
+        });
+    }
+
+    Ok(solvables)
+}
+
+/// Returns a `zypper` command that prints xml and never asks anything.
+fn zypper() -> Command {
+    let mut zypper = command("zypper");
+    zypper.arg("--xmlout").arg("--non-interactive");
+    zypper
+}
+
+/// Checks if a package is available or installed using the `zypper` package manager.
+pub fn check_zypper(package_name: &str) -> Result<PackageResult, String> {
+    // ----------------------------------------------------
+    // 1. search every version of the package in all repos
+    // ----------------------------------------------------
+    let solvables: Vec<Solvable> = search(&["--details", "--match-exact", package_name])?
+        .into_iter()
+        .filter(|solvable| solvable.name == package_name)
+        .collect();
+
+    // ------------------------------------------------------
+    // 2. get info about package: newest version, description
+    // ------------------------------------------------------
+    // repos and arches are mixed in the list, so the newest is picked by version,
+    // and it's only an update if it's newer than the installed one
+    let installed = solvables.iter().find(|s| s.status == "installed");
+    let newest = solvables
+        .iter()
+        .max_by(|a, b| Scheme::Rpm.compare(&a.edition, &b.edition))
+        .filter(|newest| {
+            installed.is_none_or(|installed| {
+                Scheme::Rpm
+                    .compare(&newest.edition, &installed.edition)
+                    .is_gt()
+            })
+        });
+
+    match (installed, newest) {
+        (Some(installed), Some(newest)) => Ok(PackageResult::installed(
+            "zypper",
+            package_name,
+            &installed.edition,
+            &newest.edition,
+            &installed.summary,
+            &newest.repository,
+        )),
+        // the installed version is the newest one, it may not be from a repo
+        (Some(installed), None) => Ok(PackageResult::installed(
+            "zypper",
+            package_name,
+            &installed.edition,
+            "",
+            &installed.summary,
+            &installed.repository,
+        )),
+        (None, Some(newest)) => Ok(PackageResult::available(
+            "zypper",
+            package_name,
+            &newest.edition,
+            &newest.summary,
+            &newest.repository,
+        )),
+        (None, None) => Ok(PackageResult::none("zypper", package_name)),
+    }
+}
+
+/// Lists all packages installed with `zypper`.
+pub fn list_zypper() -> Result<Vec<PackageResult>, String> {
+    let solvables = search(&["--details", "--installed-only"])?;
+
+    Ok(solvables
+        .into_iter()
+        .filter(|solvable| solvable.status == "installed")
+        .map(|solvable| {
+            PackageResult::installed(
+                "zypper",
+                &solvable.name,
+                &solvable.edition,
+                "",
+                &solvable.summary,
+                &solvable.repository,
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,329 @@
+use crate::{PackageResult, PackageStatus};
+use console::style;
+use serde::Serialize;
+use std::io::{self, IsTerminal, Write};
+
+/// Version of the JSON schema, bumped on breaking changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The ways boss can print its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// pretty cliclack output
+    Pretty,
+    /// plain text, one tab separated line per result
+    Plain,
+    /// a markdown table
+    Markdown,
+    /// an aligned table without box drawing
+    Table,
+    /// a single JSON document
+    Json,
+    /// one JSON object per line
+    Ndjson,
+}
+
+impl OutputFormat {
+    /// Names accepted by `--output`.
+    pub const NAMES: &'static [&'static str] =
+        &["pretty", "plain", "markdown", "table", "json", "ndjson"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pretty" => Some(OutputFormat::Pretty),
+            "plain" => Some(OutputFormat::Plain),
+            "markdown" => Some(OutputFormat::Markdown),
+            "table" => Some(OutputFormat::Table),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// The format to use if none was asked for: pretty in a terminal, plain in a pipe.
+    pub fn detect() -> Self {
+        if io::stdout().is_terminal() {
+            OutputFormat::Pretty
+        } else {
+            OutputFormat::Plain
+        }
+    }
+
+    /// Returns true for formats meant for other programs.
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Ndjson)
+    }
+
+    /// Returns the renderer for this format.
+    pub fn renderer(&self) -> &'static dyn Renderer {
+        match self {
+            OutputFormat::Pretty => &Pretty,
+            OutputFormat::Plain => &Plain,
+            OutputFormat::Markdown => &Markdown,
+            OutputFormat::Table => &Table,
+            OutputFormat::Json => &Json,
+            OutputFormat::Ndjson => &Ndjson,
+        }
+    }
+}
+
+/// Prints a list of results in a specific format.
+pub trait Renderer {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()>;
+}
+
+/// Returns the text for an optional field, `-` if it's missing.
+fn or_dash(value: &str) -> &str {
+    if value.is_empty() {
+        "-"
+    } else {
+        value
+    }
+}
+
+/// Makes a field fit into one cell of the plain or markdown output: tabs and
+/// line breaks become spaces, and for markdown `|` is escaped.
+fn cell(value: &str, format: OutputFormat) -> String {
+    let value = value
+        .split(['\t', '\n', '\r'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    match format {
+        OutputFormat::Markdown => value.replace('|', "\\|"),
+        _ => value,
+    }
+}
+
+/// Returns true if the results belong to more than one queried package.
+fn has_many_queries(results: &[PackageResult]) -> bool {
+    results
+        .iter()
+        .any(|result| result.query != results[0].query)
+}
+
+/// Pretty output using cliclack, meant to be framed by an intro and outro.
+pub struct Pretty;
+
+impl Renderer for Pretty {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let grouped = has_many_queries(results);
+        for (index, result) in results.iter().enumerate() {
+            // results are sorted by package, so a new query starts a new group
+            if grouped && (index == 0 || results[index - 1].query != result.query) {
+                cliclack::log::step(style(&result.query).cyan().bold())?;
+            }
+            // similar names come after the exact results of their query
+            if result.distance.is_some() {
+                if index == 0
+                    || results[index - 1].query != result.query
+                    || results[index - 1].distance.is_none()
+                {
+                    cliclack::log::remark(format!("similar to {}:", result.query))?;
+                }
+                cliclack::log::info(format!(
+                    "[ {} ] - {} - [{}] - ({})",
+                    result.manager,
+                    style(&result.package).cyan(),
+                    result.status,
+                    result.version_info()
+                ))?;
+                continue;
+            }
+            match result.status {
+                PackageStatus::Installed
+                | PackageStatus::InstalledOutdated
+                | PackageStatus::InstalledAutomatic => {
+                    let mut details = match &result.location {
+                        Some(location) => format!(" - {}", location),
+                        None => "".to_string(),
+                    };
+                    if result.pinned {
+                        details.push_str(" - pinned");
+                    }
+                    cliclack::log::success(format!(
+                        "[ {} ] - [{}] - ({}){}",
+                        result.manager,
+                        result.status,
+                        result.version_info(),
+                        details
+                    ))?;
+                }
+                PackageStatus::Available => {
+                    cliclack::note(
+                        format!(
+                            "[ {} ] - [available] - ({})",
+                            result.manager,
+                            result.version_info()
+                        ),
+                        &result.desc,
+                    )?;
+                }
+                PackageStatus::Virtual => {
+                    cliclack::log::warning(format!("[ {} ] - [virtual]", result.manager))?;
+                }
+                PackageStatus::NotFound => {
+                    cliclack::log::error(format!("[ {} ] - [not found]", result.manager))?;
+                }
+                PackageStatus::Error => {
+                    cliclack::log::error(format!(
+                        "[ {} ] - [error] - {}",
+                        result.manager,
+                        result.error.as_deref().unwrap_or_default()
+                    ))?;
+                }
+                PackageStatus::TimedOut => {
+                    cliclack::log::warning(format!("[ {} ] - [timed out]", result.manager))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Plain text without any decoration, one tab separated line per result:
+/// manager, package, status, version and description (or the error).
+pub struct Plain;
+
+impl Renderer for Plain {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for result in results {
+            let plain = |value: &str| cell(value, OutputFormat::Plain);
+            writeln!(
+                stdout,
+                "{}\t{}\t{}\t{}\t{}",
+                plain(&result.manager),
+                plain(&result.package),
+                result.status.as_str(),
+                or_dash(&plain(&result.version_info())),
+                or_dash(&plain(result.error.as_deref().unwrap_or(&result.desc)))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A markdown table, e.g. for issues and notes.
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        writeln!(
+            stdout,
+            "| manager | package | status | installed | latest | description |"
+        )?;
+        writeln!(stdout, "| --- | --- | --- | --- | --- | --- |")?;
+        for result in results {
+            let markdown = |value: &str| cell(value, OutputFormat::Markdown);
+            writeln!(
+                stdout,
+                "| {} | {} | {} | {} | {} | {} |",
+                markdown(&result.manager),
+                markdown(&result.package),
+                result.status,
+                or_dash(&markdown(
+                    result.installed_version.as_deref().unwrap_or_default()
+                )),
+                or_dash(&markdown(
+                    result.latest_version.as_deref().unwrap_or_default()
+                )),
+                markdown(&result.desc)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A compact table, every column padded to its longest entry.
+/// The package column is only shown when more than one package was checked
+/// or a name differs from what was asked for, similar names are marked with a `~`.
+pub struct Table;
+
+impl Renderer for Table {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let grouped = has_many_queries(results)
+            || results.iter().any(|result| result.package != result.query);
+        let mut header = vec!["manager", "status", "installed", "latest"];
+        if grouped {
+            header.insert(0, "package");
+        }
+        let header: Vec<String> = header.into_iter().map(String::from).collect();
+
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|result| {
+                let mut row = vec![
+                    result.manager.clone(),
+                    result.status.to_string(),
+                    or_dash(result.installed_version.as_deref().unwrap_or_default()).to_string(),
+                    or_dash(result.latest_version.as_deref().unwrap_or_default()).to_string(),
+                ];
+                if grouped {
+                    let package = match result.distance {
+                        Some(_) => format!("~{}", result.package),
+                        None => result.package.clone(),
+                    };
+                    row.insert(0, package);
+                }
+                row
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|column| column.len()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut stdout = io::stdout().lock();
+        for row in std::iter::once(&header).chain(&rows) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = *width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(stdout, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// The document printed by `--output json`.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    results: &'a [PackageResult],
+}
+
+/// All results as a single JSON document.
+pub struct Json;
+
+impl Renderer for Json {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let report = JsonReport {
+            schema_version: SCHEMA_VERSION,
+            results,
+        };
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, &report)?;
+        writeln!(stdout)
+    }
+}
+
+/// Every result as a JSON object on its own line.
+pub struct Ndjson;
+
+impl Renderer for Ndjson {
+    fn render(&self, results: &[PackageResult]) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        for result in results {
+            serde_json::to_writer(&mut stdout, result)?;
+            writeln!(stdout)?;
+        }
+        Ok(())
+    }
+}
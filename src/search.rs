@@ -0,0 +1,159 @@
+//! `boss search`, looks for a term with every manager that can search and
+//! merges what they find into one ranked list.
+
+use crate::cache::Cache;
+use crate::managers::{search_all, PackageManager};
+use crate::output::OutputFormat;
+use crate::similar::distance;
+use crate::{PackageResult, PackageStatus};
+use console::style;
+use std::io;
+use std::time::Duration;
+
+/// How many results are shown at once in interactive mode.
+const PAGE_SIZE: usize = 10;
+
+/// How well a result matches the term, lower is better: the exact name, then
+/// names starting with or containing the term, then the description only.
+/// Within each of them closer names come first.
+fn relevance(term: &str, result: &PackageResult) -> (u8, usize) {
+    let term = term.to_lowercase();
+    let name = result.package.to_lowercase();
+    let class = if name == term {
+        0
+    } else if name.starts_with(&term) {
+        1
+    } else if name.contains(&term) {
+        2
+    } else {
+        3
+    };
+    (class, distance(&term, &name))
+}
+
+/// Merges the results of all managers into one list, best matches first.
+/// Failed searches are returned separately.
+pub fn merge(term: &str, results: Vec<PackageResult>) -> (Vec<PackageResult>, Vec<PackageResult>) {
+    let mut merged: Vec<PackageResult> = vec![];
+    let mut failed = vec![];
+    for result in results {
+        if matches!(
+            result.status,
+            PackageStatus::Error | PackageStatus::TimedOut
+        ) {
+            failed.push(result);
+            continue;
+        }
+        // some managers list a package once per repo or architecture
+        if merged
+            .iter()
+            .any(|known| known.manager == result.manager && known.package == result.package)
+        {
+            continue;
+        }
+        merged.push(result);
+    }
+    // the sort is stable, so equal matches keep the order of the managers
+    merged.sort_by_key(|result| relevance(term, result));
+    (merged, failed)
+}
+
+/// Prints one result of a search with its name and description.
+fn print_result(result: &PackageResult) -> io::Result<()> {
+    let line = format!(
+        "[ {} ] - {} - [{}] - ({})",
+        result.manager,
+        style(&result.package).cyan(),
+        result.status,
+        result.version_info()
+    );
+    let message = if result.desc.is_empty() {
+        line
+    } else {
+        format!("{}\n{}", line, style(&result.desc).dim())
+    };
+    if result.status.is_installed() {
+        cliclack::log::success(message)
+    } else {
+        cliclack::log::info(message)
+    }
+}
+
+/// Searches all given managers for the term and prints the merged results.
+/// In interactive mode the pretty output is shown a page at a time.
+pub fn run_search(
+    term: &str,
+    managers: &[&'static dyn PackageManager],
+    cache: &Cache,
+    timeout: Duration,
+    format: OutputFormat,
+    interactive: bool,
+) -> io::Result<()> {
+    let searchers: Vec<&'static dyn PackageManager> = managers
+        .iter()
+        .copied()
+        .filter(|manager| manager.capabilities().search)
+        .collect();
+    let terms = [term.to_string()];
+
+    if format != OutputFormat::Pretty {
+        let (merged, _) = merge(term, search_all(&searchers, &terms, cache, timeout, |_| {}));
+        return format.renderer().render(&merged);
+    }
+
+    println!();
+    cliclack::intro(style(" boss ").on_cyan().black())?;
+    cliclack::log::remark(format!(
+        "Searching with: {} ({})",
+        searchers
+            .iter()
+            .map(|manager| manager.name())
+            .collect::<Vec<_>>()
+            .join(", "),
+        searchers.len()
+    ))?;
+
+    let spinner = cliclack::spinner();
+    spinner.start(format!("Searching for {}...", term));
+    let results = search_all(&searchers, &terms, cache, timeout, |manager| {
+        spinner.set_message(format!("{} done", manager));
+    });
+    let (merged, failed) = merge(term, results);
+    spinner.stop(format!("Found {} packages:", merged.len()));
+
+    for result in &failed {
+        match result.status {
+            PackageStatus::TimedOut => {
+                cliclack::log::warning(format!("[ {} ] - [timed out]", result.manager))?
+            }
+            _ => cliclack::log::error(format!(
+                "[ {} ] - [error] - {}",
+                result.manager,
+                result.error.as_deref().unwrap_or_default()
+            ))?,
+        }
+    }
+
+    let page_size = if interactive {
+        PAGE_SIZE
+    } else {
+        merged.len().max(1)
+    };
+    for (index, page) in merged.chunks(page_size).enumerate() {
+        for result in page {
+            print_result(result)?;
+        }
+        let remaining = merged.len() - (index * page_size + page.len());
+        if remaining > 0 {
+            let more = cliclack::confirm(format!("Show more? ({} left)", remaining))
+                .initial_value(true)
+                .interact()?;
+            if !more {
+                break;
+            }
+        }
+    }
+
+    cliclack::outro("Done!")?;
+    Ok(())
+}
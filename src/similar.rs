@@ -0,0 +1,156 @@
+//! Finds packages with names similar to the one asked for: known variants
+//! like `bat-cli` or `paru-bin` and near matches from the managers that can search.
+
+use crate::cache::Cache;
+use crate::managers::{query_all, search_all, PackageManager};
+use crate::{PackageResult, PackageStatus};
+use std::time::Duration;
+
+/// Suffixes that packages often get, like `paru-bin` or `yay-git` in the AUR.
+const SUFFIXES: &[&str] = &["-cli", "-bin", "-git", "-rs"];
+/// Prefixes that distros often use for libraries and language packages.
+const PREFIXES: &[&str] = &["lib", "python3-", "node-"];
+/// How long a variant that wasn't found is cached, they are only guesses and
+/// would otherwise fill the cache for the whole ttl.
+const MISS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Returns the known variants of a package name, e.g. `fd-cli` and `fd-git`
+/// for `fd`, or `fd` and `fd-cli` for `fd-git`.
+pub fn variants(package_name: &str) -> Vec<String> {
+    let base = SUFFIXES
+        .iter()
+        .find_map(|suffix| package_name.strip_suffix(suffix))
+        .or_else(|| {
+            PREFIXES
+                .iter()
+                .find_map(|prefix| package_name.strip_prefix(prefix))
+        })
+        .filter(|base| !base.is_empty())
+        .unwrap_or(package_name);
+
+    let mut variants = vec![base.to_string()];
+    variants.extend(SUFFIXES.iter().map(|suffix| format!("{}{}", base, suffix)));
+    variants.extend(PREFIXES.iter().map(|prefix| format!("{}{}", prefix, base)));
+    let mut seen = vec![package_name.to_string()];
+    variants.retain(|variant| {
+        let is_new = !seen.contains(variant);
+        seen.push(variant.clone());
+        is_new
+    });
+    variants
+}
+
+/// The Levenshtein distance, the number of single character edits to turn one into the other.
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Returns the distance of a name to the query if it's near enough: a few edits
+/// away (one per three characters) or the query is a part of it, like `ripgrep-all`.
+pub fn nearness(query: &str, name: &str) -> Option<usize> {
+    if name == query {
+        return None;
+    }
+    let distance = distance(query, name);
+    let max_distance = (query.chars().count() / 3).max(1);
+    if distance <= max_distance || name.split(['-', '_', '.']).any(|part| part == query) {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+/// Looks for similar names of all packages with all managers. The variants are
+/// checked like any package, the search results are only kept if they are near.
+///
+/// Every result has the original package as its query and a distance to it,
+/// `on_done` is called with the manager name after each check or search.
+pub fn find_similar(
+    managers: &[&'static dyn PackageManager],
+    packages: &[String],
+    cache: &Cache,
+    timeout: Duration,
+    mut on_done: impl FnMut(&str),
+) -> Vec<PackageResult> {
+    let mut similar: Vec<PackageResult> = vec![];
+    let mut push = |result: PackageResult| {
+        let is_known = similar.iter().any(|known| {
+            known.manager == result.manager
+                && known.package == result.package
+                && known.query == result.query
+        });
+        if !is_known {
+            similar.push(result);
+        }
+    };
+
+    // ---------------------------------
+    // 1. check the variants of the name
+    // ---------------------------------
+    let variants: Vec<(String, &String)> = packages
+        .iter()
+        .flat_map(|package_name| {
+            variants(package_name)
+                .into_iter()
+                .map(move |variant| (variant, package_name))
+        })
+        .collect();
+    let names: Vec<String> = variants
+        .iter()
+        .map(|(variant, _)| variant.clone())
+        .collect();
+    let results = query_all(
+        managers,
+        &names,
+        &cache.with_miss_ttl(MISS_TTL),
+        timeout,
+        |result| on_done(&result.manager),
+    );
+    // the results are in the order of the names, one per manager
+    for (index, result) in results.into_iter().enumerate() {
+        if !result.status.is_found() {
+            continue;
+        }
+        let (variant, package_name) = &variants[index / managers.len()];
+        let distance = distance(package_name, variant);
+        push(result.for_query(package_name).with_distance(distance));
+    }
+
+    // ----------------------------------------------------
+    // 2. keep the near names from managers that can search
+    // ----------------------------------------------------
+    let searchers: Vec<&'static dyn PackageManager> = managers
+        .iter()
+        .copied()
+        .filter(|manager| manager.capabilities().search)
+        .collect();
+    for result in search_all(&searchers, packages, cache, timeout, &mut on_done) {
+        if matches!(
+            result.status,
+            PackageStatus::Error | PackageStatus::TimedOut
+        ) {
+            warn!(
+                "[{}] search for {} failed: {}",
+                result.manager,
+                result.query,
+                result.error.as_deref().unwrap_or("timed out")
+            );
+            continue;
+        }
+        if let Some(distance) = nearness(&result.query, &result.package) {
+            push(result.with_distance(distance));
+        }
+    }
+
+    similar
+}
@@ -0,0 +1,833 @@
+//! Version comparison, every manager orders its versions a bit differently.
+
+use std::cmp::Ordering;
+
+use crate::managers;
+
+/// How a manager orders its versions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scheme {
+    /// `epoch:upstream-revision` like dpkg, `~` sorts before everything
+    Debian,
+    /// `epoch:version-release` like `rpmvercmp`, also close enough for pacman,
+    /// xbps and brew
+    Rpm,
+    /// `1.2.3b_rc1_p2-r1` like portage and apk, `_alpha`, `_beta`, `_pre` and `_rc`
+    /// sort before the release and `_p` after it
+    Gentoo,
+    /// PEP 440 like `1!2.0rc1.post1.dev2`, what pip and friends use
+    Pep440,
+    /// perl module versions, decimals like `1.10` (older than `1.9`) or dotted like `v1.2.3`
+    Perl,
+    /// `Gem::Version`, letters start a pre-release like `1.0.0.pre` or `1.0.0a`
+    Gem,
+    /// luarocks `version-rockrev` like `1.0rc1-2`, `scm` and `dev` are newer than everything
+    Luarocks,
+    /// semantic versions like `1.2.3-rc.1+build`, anything else is compared like rpm
+    #[default]
+    Semver,
+}
+
+impl Scheme {
+    /// Returns the scheme of the manager with the given name.
+    pub fn of(manager: &str) -> Scheme {
+        managers::find(manager)
+            .map(|manager| manager.version_scheme())
+            .unwrap_or_default()
+    }
+
+    /// Compares two versions of this scheme.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Scheme::Debian => compare_debian(a, b),
+            Scheme::Rpm => compare_rpm(a, b),
+            Scheme::Gentoo => compare_gentoo(a, b),
+            Scheme::Pep440 => compare_pep440(a, b),
+            Scheme::Perl => compare_perl(a, b),
+            Scheme::Gem => compare_gem(a, b),
+            Scheme::Luarocks => compare_luarocks(a, b),
+            Scheme::Semver => compare_semver(a, b),
+        }
+    }
+}
+
+/// Splits off the epoch and the revision (or release), the revision is
+/// everything after the last dash.
+fn split_epoch(version: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => {
+            (epoch.parse().unwrap_or(0), rest)
+        }
+        _ => (0, version),
+    };
+    let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+    (epoch, upstream, revision)
+}
+
+/// Compares two Debian versions (`epoch:upstream-revision`) like dpkg does.
+pub fn compare_debian(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_revision) = split_epoch(a);
+    let (b_epoch, b_upstream, b_revision) = split_epoch(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| verrevcmp(a_upstream, b_upstream))
+        .then_with(|| verrevcmp(a_revision, b_revision))
+}
+
+/// The weight of a character in the non digit parts: `~` sorts before
+/// everything, even the end of the string, and letters before other characters.
+fn order(c: Option<u8>) -> i32 {
+    match c {
+        Some(b'~') => -1,
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// `verrevcmp` from dpkg, compares alternating non digit and digit parts.
+fn verrevcmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let ordering = order(a.get(i).copied()).cmp(&order(b.get(j).copied()));
+            if ordering.is_ne() {
+                return ordering;
+            }
+            i += 1;
+            j += 1;
+        }
+        while i < a.len() && a[i] == b'0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == b'0' {
+            j += 1;
+        }
+        let mut first_difference = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_difference.is_eq() {
+                first_difference = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_difference.is_ne() {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compares two rpm versions (`epoch:version-release`) like rpm does.
+pub fn compare_rpm(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_version, a_release) = split_epoch(a);
+    let (b_epoch, b_version, b_release) = split_epoch(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| rpmvercmp(a_version, b_version))
+        .then_with(|| rpmvercmp(a_release, b_release))
+}
+
+/// `rpmvercmp` from rpm, compares the alphabetic and numeric segments one by one,
+/// anything else only separates them. `~` sorts before and `^` after the end.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let is_separator = |c: &u8| !c.is_ascii_alphanumeric() && *c != b'~' && *c != b'^';
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        while a.first().is_some_and(is_separator) {
+            a = &a[1..];
+        }
+        while b.first().is_some_and(is_separator) {
+            b = &b[1..];
+        }
+
+        // a tilde comes before everything
+        match (a.first(), b.first()) {
+            (Some(b'~'), Some(b'~')) => {
+                (a, b) = (&a[1..], &b[1..]);
+                continue;
+            }
+            (Some(b'~'), _) => return Ordering::Less,
+            (_, Some(b'~')) => return Ordering::Greater,
+            _ => {}
+        }
+        // a caret comes after the end, but before everything else
+        match (a.first(), b.first()) {
+            (Some(b'^'), Some(b'^')) => {
+                (a, b) = (&a[1..], &b[1..]);
+                continue;
+            }
+            (Some(b'^'), None) => return Ordering::Greater,
+            (None, Some(b'^')) => return Ordering::Less,
+            (Some(b'^'), _) => return Ordering::Less,
+            (_, Some(b'^')) => return Ordering::Greater,
+            _ => {}
+        }
+        if a.is_empty() || b.is_empty() {
+            break;
+        }
+
+        let numeric = a[0].is_ascii_digit();
+        let segment = |s: &[u8]| -> usize {
+            s.iter()
+                .position(|c| {
+                    if numeric {
+                        !c.is_ascii_digit()
+                    } else {
+                        !c.is_ascii_alphabetic()
+                    }
+                })
+                .unwrap_or(s.len())
+        };
+        let (a_end, b_end) = (segment(a), segment(b));
+        let (a_segment, b_segment) = (&a[..a_end], &b[..b_end]);
+        (a, b) = (&a[a_end..], &b[b_end..]);
+
+        // different kinds of segments, numbers are newer
+        if b_segment.is_empty() {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        let ordering = if numeric {
+            let a_segment = trim_zeros(a_segment);
+            let b_segment = trim_zeros(b_segment);
+            a_segment
+                .len()
+                .cmp(&b_segment.len())
+                .then_with(|| a_segment.cmp(b_segment))
+        } else {
+            a_segment.cmp(b_segment)
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    // whichever has segments left is newer
+    a.len().min(1).cmp(&b.len().min(1))
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|c| *c != b'0')
+        .unwrap_or(digits.len());
+    &digits[start..]
+}
+
+/// A parsed semantic version, the core can have any number of parts.
+struct Semver<'a> {
+    core: Vec<u64>,
+    pre_release: Option<&'a str>,
+}
+
+fn parse_semver(version: &str) -> Option<Semver<'_>> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    // build metadata doesn't count
+    let version = version.split('+').next().unwrap_or_default();
+    let (core, pre_release) = match version.split_once('-') {
+        Some((core, pre_release)) => (core, Some(pre_release)),
+        None => (version, None),
+    };
+    let core = core
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some(Semver { core, pre_release })
+}
+
+/// Compares two semantic versions, falls back to rpm ordering if either isn't one.
+pub fn compare_semver(a: &str, b: &str) -> Ordering {
+    let (Some(a_semver), Some(b_semver)) = (parse_semver(a), parse_semver(b)) else {
+        return compare_rpm(a, b);
+    };
+    let length = a_semver.core.len().max(b_semver.core.len());
+    for index in 0..length {
+        // missing parts count as 0, so 1.2 is 1.2.0
+        let a_part = a_semver.core.get(index).copied().unwrap_or(0);
+        let b_part = b_semver.core.get(index).copied().unwrap_or(0);
+        if a_part != b_part {
+            return a_part.cmp(&b_part);
+        }
+    }
+    // a pre-release comes before its release
+    match (a_semver.pre_release, b_semver.pre_release) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_pre_release(a, b),
+    }
+}
+
+/// Compares pre-releases like `alpha.1` and `rc.2` identifier by identifier,
+/// numeric ones numerically and before alphanumeric ones.
+fn compare_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a_identifiers = a.split('.');
+    let mut b_identifiers = b.split('.');
+    loop {
+        let ordering = match (a_identifiers.next(), b_identifiers.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// A parsed Gentoo (or apk) version like `1.2.3b_rc1_p2-r1`.
+struct Gentoo<'a> {
+    numbers: Vec<&'a str>,
+    letter: Option<char>,
+    /// the rank of each suffix and its number
+    suffixes: Vec<(usize, u64)>,
+    revision: u64,
+}
+
+/// The suffixes in their order, the release itself sorts where `""` is.
+/// apk knows a few more than portage after the release.
+const GENTOO_SUFFIXES: &[&str] = &[
+    "alpha", "beta", "pre", "rc", "", "cvs", "svn", "git", "hg", "p",
+];
+
+/// Where a version without any suffix sorts between the suffixes.
+fn release_rank() -> usize {
+    GENTOO_SUFFIXES
+        .iter()
+        .position(|suffix| suffix.is_empty())
+        .unwrap_or_default()
+}
+
+fn parse_gentoo(version: &str) -> Option<Gentoo<'_>> {
+    let (version, revision) = match version.rsplit_once("-r") {
+        Some((version, revision)) => (version, revision.parse().ok()?),
+        None => (version, 0),
+    };
+    let mut parts = version.split('_');
+    let mut numbers: Vec<&str> = parts.next()?.split('.').collect();
+    let last = numbers.pop()?;
+    let (last, letter) = match last.chars().last() {
+        Some(c) if c.is_ascii_lowercase() => (&last[..last.len() - 1], Some(c)),
+        _ => (last, None),
+    };
+    numbers.push(last);
+    if numbers
+        .iter()
+        .any(|number| number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+    let suffixes = parts
+        .map(|suffix| {
+            let digits = suffix
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(suffix.len());
+            let rank = GENTOO_SUFFIXES
+                .iter()
+                .position(|known| !known.is_empty() && *known == &suffix[..digits])?;
+            let number = match &suffix[digits..] {
+                "" => 0,
+                number => number.parse().ok()?,
+            };
+            Some((rank, number))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Gentoo {
+        numbers,
+        letter,
+        suffixes,
+        revision,
+    })
+}
+
+/// Compares two Gentoo versions like portage does (apk orders them the same),
+/// falls back to rpm ordering if either isn't one.
+pub fn compare_gentoo(a: &str, b: &str) -> Ordering {
+    let (Some(a_version), Some(b_version)) = (parse_gentoo(a), parse_gentoo(b)) else {
+        return compare_rpm(a, b);
+    };
+    for (index, (a_number, b_number)) in
+        a_version.numbers.iter().zip(&b_version.numbers).enumerate()
+    {
+        // after the first one, a leading zero makes it a fraction, so 1.01 is older than 1.1
+        let ordering = if index > 0 && (a_number.starts_with('0') || b_number.starts_with('0')) {
+            a_number
+                .trim_end_matches('0')
+                .cmp(b_number.trim_end_matches('0'))
+        } else {
+            let a_number = a_number.trim_start_matches('0');
+            let b_number = b_number.trim_start_matches('0');
+            a_number
+                .len()
+                .cmp(&b_number.len())
+                .then_with(|| a_number.cmp(b_number))
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    let ordering = a_version
+        .numbers
+        .len()
+        .cmp(&b_version.numbers.len())
+        .then_with(|| a_version.letter.cmp(&b_version.letter));
+    if ordering.is_ne() {
+        return ordering;
+    }
+    // a missing suffix is the release, so 1.0_rc1 is older than 1.0 and 1.0_p1 newer
+    let length = a_version.suffixes.len().max(b_version.suffixes.len());
+    for index in 0..length {
+        let release = (release_rank(), 0);
+        let a_suffix = a_version.suffixes.get(index).copied().unwrap_or(release);
+        let b_suffix = b_version.suffixes.get(index).copied().unwrap_or(release);
+        if a_suffix != b_suffix {
+            return a_suffix.cmp(&b_suffix);
+        }
+    }
+    a_version.revision.cmp(&b_version.revision)
+}
+
+/// A parsed PEP 440 version, the local part after `+` doesn't count.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Pep440 {
+    epoch: u64,
+    /// without trailing zeros, so 1.0 is 1
+    release: Vec<u64>,
+    /// `a`, `b` and `rc` are 0, 1 and 2, a dev release without a pre-release
+    /// comes before all of them and the final release after them
+    pre_release: (i8, u64),
+    post_release: Option<u64>,
+    /// the release itself comes after its dev releases
+    dev_release: (bool, u64),
+}
+
+fn parse_pep440(version: &str) -> Option<Pep440> {
+    let version = version.trim().to_lowercase();
+    let version = version.strip_prefix('v').unwrap_or(&version);
+    let version = version.split('+').next().unwrap_or_default();
+    let (epoch, version) = match version.split_once('!') {
+        Some((epoch, version)) => (epoch.parse().ok()?, version),
+        None => (0, version),
+    };
+
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    let (release, mut rest) = (version[..end].trim_end_matches('.'), &version[end..]);
+    let mut release = release
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    while release.last() == Some(&0) {
+        release.pop();
+    }
+
+    let (mut pre_release, mut post_release, mut dev_release) = (None, None, None);
+    // `1.0-1` is short for `1.0.post1`
+    if let Some(number) = rest.strip_prefix('-')
+        && number.chars().all(|c| c.is_ascii_digit())
+        && !number.is_empty()
+    {
+        post_release = Some(number.parse().ok()?);
+        rest = "";
+    }
+    let rest: String = rest
+        .chars()
+        .filter(|c| !matches!(c, '.' | '-' | '_'))
+        .collect();
+    let mut rest = rest.as_str();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let word = &rest[..digits];
+        let end = rest[digits..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(rest.len(), |end| digits + end);
+        let number = match &rest[digits..end] {
+            "" => 0,
+            number => number.parse().ok()?,
+        };
+        match word {
+            "a" | "alpha" => pre_release = Some((0, number)),
+            "b" | "beta" => pre_release = Some((1, number)),
+            "c" | "rc" | "pre" | "preview" => pre_release = Some((2, number)),
+            "post" | "rev" | "r" => post_release = Some(number),
+            "dev" => dev_release = Some(number),
+            _ => return None,
+        }
+        rest = &rest[end..];
+    }
+
+    let pre_release = match (pre_release, post_release, dev_release) {
+        (Some(pre_release), _, _) => pre_release,
+        (None, None, Some(_)) => (-1, 0),
+        _ => (3, 0),
+    };
+    Some(Pep440 {
+        epoch,
+        release,
+        pre_release,
+        post_release,
+        dev_release: (dev_release.is_none(), dev_release.unwrap_or(0)),
+    })
+}
+
+/// Compares two PEP 440 versions, falls back to rpm ordering if either isn't one.
+pub fn compare_pep440(a: &str, b: &str) -> Ordering {
+    match (parse_pep440(a), parse_pep440(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => compare_rpm(a, b),
+    }
+}
+
+/// Parses a perl module version into its parts, the decimal `1.10` is `1.100`
+/// and `1.9` is `1.900`, dotted versions like `v1.2.3` are taken as they are.
+fn parse_perl(version: &str) -> Option<Vec<u64>> {
+    // `_` only marks a developer release
+    let version = version.trim().replace('_', "");
+    let mut parts = match version.strip_prefix('v') {
+        Some(dotted) => dotted
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?,
+        None if version.matches('.').count() > 1 => version
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?,
+        None => {
+            let (integer, fraction) = version.split_once('.').unwrap_or((&version, ""));
+            if !fraction.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let mut parts = vec![integer.parse().ok()?];
+            let fraction = format!(
+                "{:0<width$}",
+                fraction,
+                width = fraction.len().div_ceil(3) * 3
+            );
+            for index in (0..fraction.len()).step_by(3) {
+                parts.push(fraction[index..index + 3].parse().ok()?);
+            }
+            parts
+        }
+    };
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    Some(parts)
+}
+
+/// Compares two perl module versions like `version.pm`, falls back to rpm ordering
+/// if either isn't one.
+pub fn compare_perl(a: &str, b: &str) -> Ordering {
+    match (parse_perl(a), parse_perl(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => compare_rpm(a, b),
+    }
+}
+
+/// A segment of a gem version, strings come before numbers.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum GemSegment<'a> {
+    String(&'a str),
+    Number(u64),
+}
+
+/// Splits a gem version into its segments, `1.0.0a1` is `1.0.0.a.1`
+/// and a dash starts a pre-release like in `Gem::Version`.
+fn gem_segments(version: &str) -> Option<Vec<GemSegment<'_>>> {
+    let mut segments = vec![];
+    for part in version.trim().split(['.', '-']) {
+        let mut rest = part;
+        while !rest.is_empty() {
+            let numeric = rest.starts_with(|c: char| c.is_ascii_digit());
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != numeric)
+                .unwrap_or(rest.len());
+            let (segment, tail) = rest.split_at(end);
+            segments.push(if numeric {
+                GemSegment::Number(segment.parse().ok()?)
+            } else if segment.chars().all(|c| c.is_ascii_alphabetic()) {
+                GemSegment::String(segment)
+            } else {
+                return None;
+            });
+            rest = tail;
+        }
+    }
+    if version.contains('-') {
+        // `1.0-1` is `1.0.pre.1`
+        let index = version.split('-').next()?.split('.').count();
+        segments.insert(index.min(segments.len()), GemSegment::String("pre"));
+    }
+    Some(segments)
+}
+
+/// Compares two gem versions like `Gem::Version`, falls back to rpm ordering
+/// if either isn't one.
+pub fn compare_gem(a: &str, b: &str) -> Ordering {
+    let (Some(a_segments), Some(b_segments)) = (gem_segments(a), gem_segments(b)) else {
+        return compare_rpm(a, b);
+    };
+    let length = a_segments.len().max(b_segments.len());
+    for index in 0..length {
+        // missing segments count as 0, so 1.0 is 1.0.0 but newer than 1.0.a
+        let zero = GemSegment::Number(0);
+        let a_segment = a_segments.get(index).unwrap_or(&zero);
+        let b_segment = b_segments.get(index).unwrap_or(&zero);
+        if a_segment != b_segment {
+            return a_segment.cmp(b_segment);
+        }
+    }
+    Ordering::Equal
+}
+
+/// The weights of the words in luarocks versions, unknown ones are small numbers.
+const LUAROCKS_WORDS: &[(&str, f64)] = &[
+    ("dev", 120000000.0),
+    ("scm", 110000000.0),
+    ("cvs", 100000000.0),
+    ("rc", -1000.0),
+    ("pre", -10000.0),
+    ("beta", -100000.0),
+    ("alpha", -1000000.0),
+];
+
+/// Parses a luarocks version into its numbers and the rock revision, like
+/// `parse_version` of luarocks does: every word takes a place of its own.
+fn parse_luarocks(version: &str) -> Option<(Vec<f64>, u64)> {
+    let version = version.trim().to_lowercase();
+    let (version, revision) = match version.rsplit_once('-') {
+        Some((version, revision)) => (version.to_string(), revision.parse().ok()?),
+        None => (version, 0),
+    };
+    let mut parts = vec![];
+    let mut rest = version.as_str();
+    while !rest.is_empty() {
+        let numeric = rest.starts_with(|c: char| c.is_ascii_digit());
+        let end = rest
+            .find(|c: char| {
+                if numeric {
+                    !c.is_ascii_digit()
+                } else {
+                    !c.is_ascii_alphabetic()
+                }
+            })
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        let token = &rest[..end];
+        parts.push(if numeric {
+            token.parse().ok()?
+        } else {
+            LUAROCKS_WORDS
+                .iter()
+                .find(|(word, _)| *word == token)
+                .map_or(f64::from(token.as_bytes()[0]) / 1000.0, |(_, weight)| {
+                    *weight
+                })
+        });
+        rest = rest[end..].trim_start_matches(['.', '-', '_']);
+    }
+    Some((parts, revision))
+}
+
+/// Compares two luarocks versions, falls back to rpm ordering if either isn't one.
+pub fn compare_luarocks(a: &str, b: &str) -> Ordering {
+    let (Some((a_parts, a_revision)), Some((b_parts, b_revision))) =
+        (parse_luarocks(a), parse_luarocks(b))
+    else {
+        return compare_rpm(a, b);
+    };
+    let length = a_parts.len().max(b_parts.len());
+    for index in 0..length {
+        let a_part = a_parts.get(index).copied().unwrap_or(0.0);
+        let b_part = b_parts.get(index).copied().unwrap_or(0.0);
+        if a_part != b_part {
+            return a_part.total_cmp(&b_part);
+        }
+    }
+    a_revision.cmp(&b_revision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering::{Equal, Greater, Less};
+
+    fn check(compare: fn(&str, &str) -> Ordering, table: &[(&str, &str, Ordering)]) {
+        for (a, b, expected) in table {
+            assert_eq!(compare(a, b), *expected, "{} vs {}", a, b);
+            assert_eq!(compare(b, a), expected.reverse(), "{} vs {}", b, a);
+        }
+    }
+
+    #[test]
+    fn debian() {
+        check(
+            compare_debian,
+            &[
+                ("1.0", "1.0", Equal),
+                ("1.0", "1.1", Less),
+                ("1.10", "1.9", Greater),
+                ("1.0~rc1", "1.0", Less),
+                ("1.0~~", "1.0~", Less),
+                ("1.0~rc1", "1.0~rc2", Less),
+                ("1.0a", "1.0", Greater),
+                ("1.0+b1", "1.0", Greater),
+                ("1:1.0", "2.0", Greater),
+                ("1:1.0", "1:1.1", Less),
+                ("1.0-1", "1.0-2", Less),
+                ("1.0-10", "1.0-9", Greater),
+                ("1.0-1ubuntu1", "1.0-1", Greater),
+                ("1.0-1~bpo1", "1.0-1", Less),
+                ("2.30-1+deb12u1", "2.30-1", Greater),
+            ],
+        );
+    }
+
+    #[test]
+    fn rpm() {
+        check(
+            compare_rpm,
+            &[
+                ("1.0", "1.0", Equal),
+                ("1.0", "1.0.1", Less),
+                ("1.10", "1.9", Greater),
+                ("1.01", "1.1", Equal),
+                ("1.0~rc1", "1.0", Less),
+                ("1.0~rc1", "1.0~rc2", Less),
+                ("1.0^git1", "1.0", Greater),
+                ("1.0^git1", "1.0.1", Less),
+                ("1.0a", "1.0", Greater),
+                ("1.0a", "1.0.1", Less),
+                ("1:1.0", "2.0", Greater),
+                ("1.0-1", "1.0-2", Less),
+                ("1.0-1.fc40", "1.0-1.fc39", Greater),
+                ("1.0_rc1", "1.0", Greater),
+            ],
+        );
+    }
+
+    #[test]
+    fn gentoo() {
+        check(
+            compare_gentoo,
+            &[
+                ("1.0", "1.0", Equal),
+                ("1.0", "1.0.0", Less),
+                ("1.10", "1.9", Greater),
+                ("1.01", "1.1", Less),
+                ("1.0_rc1", "1.0", Less),
+                ("1.0_alpha", "1.0_beta", Less),
+                ("1.0_pre1", "1.0_rc1", Less),
+                ("1.0_rc1", "1.0_rc2", Less),
+                ("1.0_p1", "1.0", Greater),
+                ("1.0_rc1_p1", "1.0_rc1", Greater),
+                ("1.0_rc1_p1", "1.0", Less),
+                ("1.0a", "1.0", Greater),
+                ("1.0a", "1.0_p1", Greater),
+                ("1.0-r1", "1.0", Greater),
+                ("1.0-r10", "1.0-r9", Greater),
+                ("1.0_rc1-r3", "1.0", Less),
+                ("1.0_git20240101-r0", "1.0", Greater),
+            ],
+        );
+    }
+
+    #[test]
+    fn pep440() {
+        check(
+            compare_pep440,
+            &[
+                ("1.0", "1.0.0", Equal),
+                ("1.10", "1.9", Greater),
+                ("1.0rc1", "1.0", Less),
+                ("1.0a1", "1.0b1", Less),
+                ("1.0b2", "1.0rc1", Less),
+                ("1.0.dev1", "1.0a1", Less),
+                ("1.0a1.dev1", "1.0a1", Less),
+                ("1.0", "1.0.post1", Less),
+                ("1.0-1", "1.0.post1", Equal),
+                ("1.0.post1", "1.1.dev1", Less),
+                ("1!1.0", "2.0", Greater),
+                ("1.0+local", "1.0", Equal),
+                ("1.0-rc.1", "1.0rc1", Equal),
+            ],
+        );
+    }
+
+    #[test]
+    fn perl() {
+        check(
+            compare_perl,
+            &[
+                ("1.10", "1.9", Less),
+                ("1.10", "1.1", Equal),
+                ("1.001", "1.01", Less),
+                ("1.23_01", "1.2301", Equal),
+                ("v1.2.3", "1.2.3", Equal),
+                ("v1.2.3", "v1.10.0", Less),
+                ("1.002003", "v1.2.3", Equal),
+                ("2", "1.999", Greater),
+            ],
+        );
+    }
+
+    #[test]
+    fn gem() {
+        check(
+            compare_gem,
+            &[
+                ("1.0", "1.0.0", Equal),
+                ("1.10", "1.9", Greater),
+                ("1.0.0.pre", "1.0.0", Less),
+                ("1.0.0.pre1", "1.0.0.pre2", Less),
+                ("1.0.0a", "1.0.0", Less),
+                ("1.0.0.rc1", "1.0.0.beta2", Greater),
+                ("1.0.0.pre", "0.9", Greater),
+                ("1.0-1", "1.0", Less),
+                ("1.0-1", "1.0.pre.1", Equal),
+            ],
+        );
+    }
+
+    #[test]
+    fn luarocks() {
+        check(
+            compare_luarocks,
+            &[
+                ("1.0-1", "1.0-1", Equal),
+                ("1.0-1", "1.0-2", Less),
+                ("1.10-1", "1.9-1", Greater),
+                ("1.0rc1-1", "1.0-1", Less),
+                ("1.0alpha-1", "1.0beta-1", Less),
+                ("scm-1", "1.0-1", Greater),
+                ("dev-1", "scm-1", Greater),
+                ("1.0.1-1", "1.0-3", Greater),
+            ],
+        );
+    }
+}